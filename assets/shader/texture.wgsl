@@ -9,6 +9,7 @@ struct InstanceInput {
     @location(6) model_matrix_1: vec4<f32>,
     @location(7) model_matrix_2: vec4<f32>,
     @location(8) model_matrix_3: vec4<f32>,
+    @location(9) tint: vec3<f32>,
 };
 
 @group(0) @binding(0) // 1.
@@ -22,6 +23,7 @@ struct VertexInput {
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) tex_coords: vec2<f32>,
+    @location(1) tint: vec3<f32>,
 }
 
 @vertex
@@ -42,6 +44,7 @@ fn vs_main(
     instance.model_texcoord[0] * model.tex_coords[0] + instance.model_texcoord[1] * (1.0-model.tex_coords[0])  ,
     instance.model_texcoord[2] * model.tex_coords[1] + instance.model_texcoord[3] * (1.0-model.tex_coords[1])
     );// model.tex_coords + instance.model_texcoord;
+    out.tint = instance.tint;
     out.clip_position =  camera.view_proj *model_matrix * vec4<f32>(model.position, 1.0);
     return out;
 }
@@ -61,5 +64,5 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
         discard;
     }
 
-    return texture;
+    return vec4<f32>(texture.rgb * in.tint, texture.a);
 }
\ No newline at end of file
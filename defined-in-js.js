@@ -7,11 +7,10 @@ canvas.width = "500"
 canvas.height = "900"
 const ctx = canvas.getContext('2d');
 
-const layerSizes = [2, 6, 4, 1];
-const spaceBetweenLayers = 220;
+let layerSizes = [];
+let spaceBetweenLayers = 220;
 const neuronRadius = 30;
 
-let init = false;
 let network = [];
 
 let neuron = (_layer, _index,  _before)=>{
@@ -32,24 +31,10 @@ let neuron = (_layer, _index,  _before)=>{
         return `rgba(${r},${g},0,1)`;
     }
 
-    function calculate_value(genes){
-        let v = 0;
-        for (let i = 0; i < _before.length; i++) {
-            v += _before[i].value() * genes[i];
-        }
-        v += genes[genes.length -1];
-        value = v;
-        if ( isNaN(v)){
-            console.log();
-        }
-    }
     function _render(genes){
         if (genes === undefined){
             genes = [0,0,0];
         }
-        else {
-            calculate_value(genes);
-        }
 
         let gene_counter = 0;
         let numNeurons = layerSizes[_layer];
@@ -91,39 +76,41 @@ let neuron = (_layer, _index,  _before)=>{
         render : _render
     }
 }
-function initialize(){
-    if ( init )return;
-    init = true;
+function initialize(layers){
+    if ( JSON.stringify(layers) === JSON.stringify(layerSizes) )return;
+    layerSizes = layers;
+    spaceBetweenLayers = canvas.height / layers.length;
+    network = [];
 
-    network.push(neuron(0,0,[]));
-    network.push(neuron(0,1,[]));
-    network.push(neuron(1,0,[network[0],network[1]]));
-    network.push(neuron(1,1,[network[0],network[1]]));
-    network.push(neuron(1,2,[network[0],network[1]]));
-    network.push(neuron(1,3,[network[0],network[1]]));
-    network.push(neuron(1,4,[network[0],network[1]]));
-    network.push(neuron(1,5,[network[0],network[1]]));
-    network.push(neuron(2,0,[network[2],network[3],network[4],network[5],network[6],network[7]]));
-    network.push(neuron(2,1,[network[2],network[3],network[4],network[5],network[6],network[7]]));
-    network.push(neuron(2,2,[network[2],network[3],network[4],network[5],network[6],network[7]]));
-    network.push(neuron(2,3,[network[2],network[3],network[4],network[5],network[6],network[7]]));
-    network.push(neuron(3,0,[network[8],network[9],network[10],network[11]]));
+    let before = [];
+    for (let layer = 0; layer < layers.length; layer++) {
+        let current = [];
+        for (let index = 0; index < layers[layer]; index++) {
+            current.push(neuron(layer, index, before));
+        }
+        network.push(...current);
+        before = current;
+    }
 }
 
-initialize();
-export function render( gene, posStr ) {
+// valuesStr holds the activated values of every layer, input layer first
+export function render( layersStr, gene, valuesStr ) {
+    initialize(JSON.parse(layersStr));
     let array = JSON.parse(gene);
-    let pos = JSON.parse(posStr);
-    network[0].setValue(pos[0]);
-    network[1].setValue(pos[1]);
+    let values = JSON.parse(valuesStr).flat();
+    let inputCount = layerSizes[0];
+    for (let i = 0; i < network.length; i++) {
+        network[i].setValue(values[i]);
+    }
 
     // Fill the canvas with the background color
     ctx.fillStyle = 'lightblue';
     ctx.fillRect(0, 0, canvas.width, canvas.height);
 
-    network[0].render();
-    network[1].render();
-    for (let i = 2; i < network.length; i++) {
+    for (let i = 0; i < inputCount; i++) {
+        network[i].render();
+    }
+    for (let i = inputCount; i < network.length; i++) {
         let neuron = network[i];
         let single_gene = array.splice(0,neuron.getGeneSize());
         neuron.render(single_gene);
@@ -0,0 +1,19 @@
+use flappy_nn::headless::HeadlessOptions;
+use flappy_nn::options::GameOptions;
+
+fn main() {
+    env_logger::init();
+
+    let options = match HeadlessOptions::from_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("usage: flappy_headless {} {}", HeadlessOptions::USAGE, GameOptions::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if let Err(e) = flappy_nn::headless::run(options) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
@@ -1,3 +1,13 @@
+use flappy_nn::options::GameOptions;
+
 fn main() {
-    pollster::block_on(flappy_nn::start());
+    let options = match GameOptions::from_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("usage: flappy {}", GameOptions::USAGE);
+            std::process::exit(2);
+        }
+    };
+    pollster::block_on(flappy_nn::start_with(options));
 }
@@ -7,16 +7,19 @@ use winit::{
     application::ApplicationHandler,
 };
 use winit::dpi::{PhysicalPosition, PhysicalSize};
-use crate::game_configs::GENE_SIZE;
 use std::sync::Arc;
 
 #[cfg(target_arch = "wasm32")]
 use crate::wasm_bindings::render;
 
 
+use crate::fixed_timestep::FixedTimestep;
 use crate::game_state::GameState;
+use crate::options::GameOptions;
 use crate::renderer::*;
 
+const MAX_SPEED_FRAME_BUDGET: f32 = 0.012;
+
 pub struct Application {
     gs : GameState,
     rs : RenderState,
@@ -26,6 +29,7 @@ pub struct Application {
 
     prev_mouse_position: PhysicalPosition<f64>,
     prev_time: Instant,
+    timestep: FixedTimestep,
 
 
 }
@@ -59,12 +63,9 @@ impl ApplicationHandler<()> for Application {
                         self.resize(new_size);
                     }
                     WindowEvent::RedrawRequested => {
-                        let elapsed_time = self.prev_time.elapsed().as_millis() as f32 / 1000.0;
+                        let elapsed_time = self.prev_time.elapsed().as_secs_f32();
                         self.prev_time = Instant::now();
 
-                        if elapsed_time > 0.2 {
-                            return;
-                        }
                         self.update(elapsed_time);
                         match self.render() {
                             Ok(_) => {}
@@ -77,9 +78,10 @@ impl ApplicationHandler<()> for Application {
                         #[cfg(target_arch = "wasm32")]
                         {
                             let arr = self.get_gene_data();
-                            let str1 = format!("{:?}", arr.0);
-                            let str2 = format!("{:?}", arr.1);
-                            render(&str1, &str2);
+                            let str0 = format!("{:?}", arr.0);
+                            let str1 = format!("{:?}", arr.1);
+                            let str2 = format!("{:?}", arr.2);
+                            render(&str0, &str1, &str2);
                         }
                     }
                     _ => {}
@@ -92,7 +94,8 @@ impl ApplicationHandler<()> for Application {
 impl Application {
     pub async fn new(
         window_attributes: winit::window::WindowAttributes,
-        event_loop: &EventLoop<()>) -> Self {
+        event_loop: &EventLoop<()>,
+        options: GameOptions) -> Self {
         let window = Arc::new(event_loop
             .create_window(window_attributes)
             .unwrap());
@@ -115,17 +118,19 @@ impl Application {
         }
 
 
+        let screen_size = options.config.screen_size;
         let size = winit::dpi::PhysicalSize::new(
-            crate::game_configs::SCREEN_SIZE[0], 
-            crate::game_configs::SCREEN_SIZE[1]
+            screen_size[0],
+            screen_size[1]
         );
         let prev_mouse_position = PhysicalPosition::new(0.0, 0.0);
         let prev_time = Instant::now();
 
-        let mut gs = GameState::default();
-        gs.init();
+        let mut gs = GameState::new(options);
+        gs.init().expect("failed to initialize game");
+        let timestep = FixedTimestep::new(gs.tick_rate());
         
-        let mut rs = RenderState::new(window.clone()).await;
+        let mut rs = RenderState::new(window.clone(), screen_size).await;
         rs.init_resources().await;
 
         Self {
@@ -135,11 +140,12 @@ impl Application {
             size,
             prev_mouse_position,
             prev_time,
+            timestep,
         }
     }
 
 
-    pub fn get_gene_data(&self) -> ([f32; GENE_SIZE], [f32;2]){
+    pub fn get_gene_data(&self) -> (Vec<usize>, Vec<f32>, Vec<Vec<f32>>){
         self.gs.get_gene_data()
     }
 
@@ -159,21 +165,39 @@ impl Application {
                 self.prev_mouse_position = position.clone();
                 true
             }
-            WindowEvent::MouseInput { state, button, .. } => {
-                match button {
-                    MouseButton::Left => {
-                        // self.toggle_full_screen();
-                    }
-                    _ => {}
+            WindowEvent::MouseInput { state, button: MouseButton::Left, .. } => {
+                self.gs.handle_pointer_input(*state)
+            }
+            WindowEvent::Touch(touch) => {
+                match touch.phase {
+                    TouchPhase::Started => self.gs.handle_pointer_input(ElementState::Pressed),
+                    TouchPhase::Ended | TouchPhase::Cancelled => self.gs.handle_pointer_input(ElementState::Released),
+                    TouchPhase::Moved => false,
                 }
-                false
             }
             _ => false,
         }
     }
 
-    fn update(&mut self, dt: f32) {
-        self.gs.update(dt);
+    fn update(&mut self, elapsed_time: f32) {
+        match self.gs.time_scale().factor() {
+            Some(factor) => {
+                let ticks = self.timestep.advance(elapsed_time, factor);
+                for _ in 0..ticks {
+                    self.gs.snapshot_positions();
+                    self.gs.update(self.timestep.step());
+                }
+            }
+            None => {
+                // spend most of a 60 fps frame simulating, then draw the latest state,
+                // but don't spin while the game waits to be started, unpaused or restarted
+                self.timestep.reset();
+                let frame_start = Instant::now();
+                while self.gs.is_running() && frame_start.elapsed().as_secs_f32() < MAX_SPEED_FRAME_BUDGET {
+                    self.gs.update(self.timestep.step());
+                }
+            }
+        }
     }
 
     fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
@@ -184,7 +208,8 @@ impl Application {
 
 
         // //2. update meshes
-        let instances = self.gs.get_tile_instance();
+        let alpha = if self.gs.is_running() && self.gs.time_scale().factor().is_some() { self.timestep.alpha() } else { 1.0 };
+        let instances = self.gs.get_tile_instance(alpha);
         self.rs.update_mesh_instance(instances);
 
 
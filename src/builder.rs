@@ -1,8 +1,6 @@
-use rand::rngs::ThreadRng;
 use specs::{Builder, World, WorldExt};
 use crate::components::*;
-use crate::game_configs::HOLE_SIZE;
-use crate::resources::GeneHandler;
+use crate::resources::{CourseRng, GameConfig, GeneHandler};
 
 pub fn background(world: &mut World) {
     world.create_entity()
@@ -52,9 +50,10 @@ pub fn background(world: &mut World) {
 pub fn pipe(world: &mut World, pos: f32) {
     use rand::Rng;
     let rand;
+    let hole_size = world.read_resource::<GameConfig>().hole_size;
     {
-        let mut rng = world.write_resource::<ThreadRng>();
-        rand = rng.gen_range(3.0..7.0);
+        let mut rng = world.write_resource::<CourseRng>();
+        rand = rng.0.gen_range(3.0..7.0);
     }
     world.create_entity()
         .with(Tile {
@@ -92,7 +91,7 @@ pub fn pipe(world: &mut World, pos: f32) {
             atlas: "tile".to_string(),
         })
         .with(Transform {
-            position: [pos, rand + HOLE_SIZE - 4.0, 0.2],
+            position: [pos, rand + hole_size - 4.0, 0.2],
             size: [2.0, 2.0],
         })
         .with(Pipe {
@@ -106,8 +105,8 @@ pub fn pipe(world: &mut World, pos: f32) {
             atlas: "tile".to_string(),
         })
         .with(Transform {
-            position: [pos, (rand + HOLE_SIZE - 4.0) * 0.5 + 5.5, 0.2],
-            size: [2.0, 13.0 - (rand + HOLE_SIZE)],
+            position: [pos, (rand + hole_size - 4.0) * 0.5 + 5.5, 0.2],
+            size: [2.0, 13.0 - (rand + hole_size)],
         })
         .with(Pipe {
             reposition_size: 16.0,
@@ -127,13 +126,38 @@ pub fn player(world: &mut World) {
             size: [1., 1.],
         })
         .with(Player::default())
+        .with(Human)
+        .with(Tint::HUMAN)
+        .with(BirdScore::default())
         .with(Animation::default())
         .build();
 }
 
+/// A bird of a replay, jumping where the recorded one did, see `PlayReplay`.
+pub fn replay_player(world: &mut World, slot: usize, human: bool) {
+    let mut builder = world.create_entity()
+        .with(Tile {
+            uv: [0.0, 0.25, 0.0, 1.0],
+            atlas: "player".to_string(),
+        })
+        .with(Transform {
+            position: [0., 0., 0.3],
+            size: [1., 1.],
+        })
+        .with(Player::default())
+        .with(BirdScore::default())
+        .with(Animation::default())
+        .with(ReplaySlot(slot));
+    if human {
+        builder = builder.with(Tint::HUMAN);
+    }
+    builder.build();
+}
+
 pub fn ai_player(world: &mut World) {
     let dna = world.write_resource::<GeneHandler>().get_dna();
-    world.create_entity()
+    let species = world.read_resource::<GeneHandler>().species_of(dna.index);
+    let mut builder = world.create_entity()
         .with(Tile {
             uv: [0.0, 0.25, 0.0, 1.0],
             atlas: "player".to_string(),
@@ -143,7 +167,11 @@ pub fn ai_player(world: &mut World) {
             size: [1., 1.],
         })
         .with(Player::default())
+        .with(BirdScore::default())
         .with(Animation::default())
-        .with(dna)
-        .build();
+        .with(dna);
+    if let Some(species) = species {
+        builder = builder.with(Tint::for_species(species));
+    }
+    builder.build();
 }
\ No newline at end of file
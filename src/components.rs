@@ -1,6 +1,8 @@
+use serde::{Deserialize, Serialize};
 use specs::*;
 use specs_derive::Component;
-use crate::game_configs::GENE_SIZE;
+
+use crate::resources::neat::NeatNetwork;
 
 
 #[allow(dead_code)]
@@ -31,6 +33,10 @@ pub struct Transform {
     pub size: [f32; 2],
 }
 
+/// Position before the latest simulation tick, used to interpolate rendering between ticks.
+#[derive(Component, Clone)]
+pub struct PreviousPosition(pub [f32; 3]);
+
 #[derive(Component, Clone)]
 pub struct Text {
     pub content: String,
@@ -57,6 +63,24 @@ pub struct Player {
     pub jump : bool,
 }
 
+/// Marks the bird flown with `InputHandler` instead of a network.
+#[derive(Component, Clone, Default)]
+pub struct Human;
+
+/// Index of the bird in the `ReplayLog`.
+#[derive(Component, Clone, Copy)]
+pub struct ReplaySlot(pub usize);
+
+/// What a bird has achieved so far this round.
+#[derive(Component, Clone, Copy, Default)]
+pub struct BirdScore {
+    pub time_alive: f32,
+    pub pipes: u32,
+    pub jumps: u32,
+    /// Jumps made while already rising.
+    pub wasted_jumps: u32,
+}
+
 #[derive(Component, Clone, Default)]
 pub struct Animation {
     pub index : u32,
@@ -76,12 +100,85 @@ pub struct NeuralLayer {
 }
 
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Activation {
+    Identity,
+    Relu,
+    LeakyRelu,
+    Tanh,
+    Sigmoid,
+    Step,
+}
+
+impl Activation {
+    pub fn apply(&self, x: f32) -> f32 {
+        match self {
+            Activation::Identity => x,
+            Activation::Relu => x.max(0.0),
+            Activation::LeakyRelu => if x > 0.0 { x } else { 0.01 * x },
+            Activation::Tanh => x.tanh(),
+            Activation::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+            Activation::Step => if x > 0.0 { 1.0 } else { 0.0 },
+        }
+    }
+
+    /// Output above which a neuron counts as firing, the middle of the activation's range.
+    pub fn threshold(&self) -> f32 {
+        match self {
+            Activation::Sigmoid | Activation::Step => 0.5,
+            _ => 0.0,
+        }
+    }
+}
+
+impl std::str::FromStr for Activation {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('-', "_").as_str() {
+            "identity" | "linear" => Ok(Activation::Identity),
+            "relu" => Ok(Activation::Relu),
+            "leaky_relu" => Ok(Activation::LeakyRelu),
+            "tanh" => Ok(Activation::Tanh),
+            "sigmoid" => Ok(Activation::Sigmoid),
+            "step" => Ok(Activation::Step),
+            _ => anyhow::bail!("unknown activation {}", s),
+        }
+    }
+}
+
 #[derive(Component, Clone)]
 pub struct DNA {
-    pub hidden_layers:[usize;2],
-    pub genes:[f32;GENE_SIZE],
+    pub genes:Vec<f32>,
     pub index:usize,
+    /// Set instead of `genes` when the population is NEAT.
+    pub graph: Option<NeatNetwork>,
 }
 
+/// Sprite color multiplier, set on birds of a speciated population so species can be told apart.
+#[derive(Component, Clone, Copy)]
+pub struct Tint(pub [f32; 3]);
+
+impl Tint {
+    /// The bird flown by a person. Deeper than any species tint, so it stands out in a race.
+    pub const HUMAN: Tint = Tint([1.0, 0.35, 0.35]);
+
+    /// A light color per species id, hues spread by the golden ratio so neighbouring ids differ.
+    pub fn for_species(id: usize) -> Self {
+        let hue = (id as f32 * 0.618_034).fract() * 6.0;
+        let x = 1.0 - (hue % 2.0 - 1.0).abs();
+        let [r, g, b] = match hue as usize {
+            0 => [1.0, x, 0.0],
+            1 => [x, 1.0, 0.0],
+            2 => [0.0, 1.0, x],
+            3 => [0.0, x, 1.0],
+            4 => [x, 0.0, 1.0],
+            _ => [1.0, 0.0, x],
+        };
+        // halfway to white keeps the sprite readable
+        Tint([0.5 + r * 0.5, 0.5 + g * 0.5, 0.5 + b * 0.5])
+    }
+}
 
 //endregion
\ No newline at end of file
@@ -0,0 +1,99 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::game_state::GameState;
+use crate::options::GameOptions;
+use crate::resources::{CourseRng, FitnessTerms, GeneHandler};
+
+/// Scores every genome on several independent courses and averages the fitness, so a single
+/// lucky pipe layout can't decide a generation.
+///
+/// Every course is split into batches of genomes, and every batch is played in its own `World`
+/// with the usual systems. Batches run on a pool of threads; results don't depend on the thread count.
+pub struct Evaluator {
+    /// Courses every genome plays, each with its own pipe layout.
+    pub courses: usize,
+    /// Genomes per world, the whole population when 0.
+    pub batch_size: usize,
+    pub threads: usize,
+}
+
+/// Per-genome results, in population order.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Evaluation {
+    pub fitness: Vec<f32>,
+    pub terms: Vec<FitnessTerms>,
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Evaluator {
+            courses: 1,
+            batch_size: 0,
+            threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+}
+
+impl Evaluator {
+    /// Average fitness and fitness terms of every genome of `gene_handler` over all courses of its current generation.
+    pub fn evaluate(&self, gene_handler: &GeneHandler, options: &GameOptions, max_time: Option<f32>) -> Evaluation {
+        let population = gene_handler.gene_count();
+        let batch_size = if self.batch_size == 0 { population } else { self.batch_size };
+        let batches: Vec<Vec<usize>> = (0..population).collect::<Vec<_>>()
+            .chunks(batch_size)
+            .map(|chunk| chunk.to_vec())
+            .collect();
+        let jobs: Vec<(usize, &Vec<usize>)> = (0..self.courses)
+            .flat_map(|course| batches.iter().map(move |batch| (course, batch)))
+            .collect();
+
+        let next_job = AtomicUsize::new(0);
+        let results = Mutex::new(vec![Evaluation::default(); jobs.len()]);
+        std::thread::scope(|scope| {
+            for _ in 0..self.threads.clamp(1, jobs.len().max(1)) {
+                scope.spawn(|| loop {
+                    let job = next_job.fetch_add(1, Ordering::Relaxed);
+                    let Some((course, batch)) = jobs.get(job) else {
+                        break;
+                    };
+                    let course_rng = CourseRng::for_course(gene_handler.seed(), gene_handler.generation, *course);
+                    let evaluation = GameState::evaluate_course(options.clone(), gene_handler.batch(batch), course_rng, max_time);
+                    results.lock().unwrap()[job] = evaluation;
+                });
+            }
+        });
+
+        // summed in job order so the average doesn't depend on which thread finished first
+        let mut total = Evaluation { fitness: vec![0.0; population], terms: vec![FitnessTerms::default(); population] };
+        for ((_, batch), evaluation) in jobs.iter().zip(results.into_inner().unwrap()) {
+            for (position, index) in batch.iter().enumerate() {
+                total.fitness[*index] += evaluation.fitness[position];
+                total.terms[*index] += &evaluation.terms[position];
+            }
+        }
+        let courses = self.courses as f32;
+        total.fitness.iter_mut().for_each(|score| *score /= courses);
+        total.terms.iter_mut().for_each(|terms| *terms /= courses);
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::resources::NetworkShape;
+    use super::*;
+
+    #[test]
+    fn test_thread_count_does_not_change_fitness() {
+        let gene_handler = GeneHandler::new(7, NetworkShape::default());
+        let options = GameOptions::default();
+        let evaluate = |threads, batch_size| Evaluator { courses: 2, batch_size, threads }
+            .evaluate(&gene_handler, &options, Some(3.0));
+
+        let evaluation = evaluate(1, 0);
+        assert_eq!(evaluation.fitness.len(), gene_handler.gene_count());
+        assert!(evaluation.fitness.iter().all(|f| *f > 0.0));
+        assert_eq!(evaluation, evaluate(3, 30));
+    }
+}
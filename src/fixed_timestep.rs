@@ -0,0 +1,57 @@
+/// Accumulates real frame time and hands it out as whole simulation ticks of a fixed length,
+/// so physics and fitness don't depend on the display refresh rate.
+pub struct FixedTimestep {
+    step: f32,
+    accumulator: f32,
+    max_frame_time: f32,
+}
+
+impl FixedTimestep {
+    pub fn new(tick_rate: u32) -> Self {
+        FixedTimestep {
+            step: 1.0 / tick_rate as f32,
+            accumulator: 0.0,
+            max_frame_time: 0.25,
+        }
+    }
+
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    /// Adds one frame of elapsed time, sped up by `time_scale`, and returns how many ticks are due.
+    /// Long hiccups are clamped so the simulation slows down instead of spiralling.
+    pub fn advance(&mut self, elapsed: f32, time_scale: f32) -> usize {
+        self.accumulator += elapsed.min(self.max_frame_time) * time_scale;
+        let ticks = (self.accumulator / self.step) as usize;
+        self.accumulator -= ticks as f32 * self.step;
+        ticks
+    }
+
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0;
+    }
+
+    /// How far the renderer is between the last two ticks, in `0.0..1.0`.
+    pub fn alpha(&self) -> f32 {
+        self.accumulator / self.step
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advance_keeps_remainder(){
+        let mut timestep = FixedTimestep::new(8);
+        assert_eq!(timestep.advance(0.0625, 1.0), 0);
+        assert_eq!(timestep.alpha(), 0.5);
+        assert_eq!(timestep.advance(0.1875, 1.0), 2);
+        assert_eq!(timestep.alpha(), 0.0);
+
+        // a long stall only catches up the clamped frame time
+        assert_eq!(timestep.advance(10.0, 1.0), 2);
+        assert_eq!(timestep.advance(0.125, 8.0), 8);
+    }
+}
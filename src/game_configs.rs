@@ -1,7 +1,39 @@
+use crate::components::Activation;
+use crate::resources::sensor_set::Sensor;
+
+// defaults for `resources::GameConfig`
 pub const HOLE_SIZE: f32 = 2.0;
-pub const GRAVITY : f32 = 0.5;
+pub const GRAVITY : f32 = 30.0;
 pub const JUMP_FORCE : f32 = 6.0;
 pub const GAME_SPEED : f32 = 5.0;
-pub const GENE_SIZE:usize = 51;
 
-pub const SCREEN_SIZE : [u32;2] = [500 , 900];
\ No newline at end of file
+// defaults for `resources::NetworkShape`
+pub const DEFAULT_SENSORS : [Sensor;2] = [Sensor::PipeDx, Sensor::PipeDy];
+pub const HIDDEN_LAYERS : [usize;2] = [6, 4];
+pub const NETWORK_OUTPUT_SIZE : usize = 1;
+pub const HIDDEN_ACTIVATION : Activation = Activation::Identity;
+pub const OUTPUT_ACTIVATION : Activation = Activation::Identity;
+
+// defaults for `resources::EvolutionConfig`
+pub const ELITE_RATIO : f32 = 0.1;
+pub const EVOLVE_OFFSET : f32 = 0.1;
+// genes start in -GENE_RANGE..GENE_RANGE
+pub const GENE_RANGE : f32 = 16.0;
+pub const SPECIES_THRESHOLD : f32 = 14.0;
+pub const MIN_SPECIES_SIZE : usize = 2;
+pub const HALL_OF_FAME_SIZE : usize = 10;
+
+// defaults for `resources::FitnessConfig`
+pub const SURVIVAL_WEIGHT : f32 = 1.0;
+pub const PIPE_WEIGHT : f32 = 0.0;
+pub const GAP_WEIGHT : f32 = 0.0;
+pub const WASTED_JUMP_PENALTY : f32 = 0.0;
+
+pub const SCREEN_SIZE : [u32;2] = [500 , 900];
+
+// birds leaving this band die
+pub const WORLD_FLOOR : f32 = -7.0;
+pub const WORLD_CEILING : f32 = 9.0;
+
+// vision rays see this far, hit distances are divided by it
+pub const RAY_LENGTH : f32 = 16.0;
\ No newline at end of file
@@ -1,16 +1,22 @@
+#[cfg(feature = "render")]
 use std::collections::HashMap;
+use std::path::Path;
 
-use rand::rngs::ThreadRng;
-use specs::{Join, World, WorldExt};
+use specs::{Entity, Join, World, WorldExt};
+#[cfg(feature = "render")]
 use winit::event::ElementState;
+#[cfg(feature = "render")]
 use winit::keyboard::{KeyCode, PhysicalKey};
-use crate::builder::{background, pipe, ai_player};
+use crate::builder::{background, pipe, ai_player, player, replay_player};
+use crate::evaluator::{Evaluation, Evaluator};
 
 use crate::components::*;
-use crate::game_configs::GENE_SIZE;
+use crate::game_configs::NETWORK_OUTPUT_SIZE;
+use crate::options::GameOptions;
 use crate::resources::*;
 use crate::system;
-use crate::system::UnifiedDispatcher;
+use crate::system::{process_network_layers, UnifiedDispatcher};
+#[cfg(feature = "render")]
 use crate::renderer::*;
 
 
@@ -24,24 +30,158 @@ impl Default for Stage {
     }
 }
 
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Copy)]
+pub enum GameMode {
+    /// Evolve the whole population every round.
+    Training,
+    /// Replay a single exported network without evolving it.
+    Champion,
+    /// A person flies one bird on the course of the current generation, the population waits.
+    Human,
+    /// A person races networks on the same course, nothing evolves.
+    Race(RaceOpponents),
+    /// Plays back a recorded round, nothing evolves.
+    Replay,
+}
+
+/// Who the person races in `GameMode::Race`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Copy)]
+pub enum RaceOpponents {
+    /// The best genome scored so far, or the network of `--play-champion`.
+    Champion,
+    /// Every bird of the current generation.
+    Population,
+}
+
+impl std::str::FromStr for RaceOpponents {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "champion" => Ok(RaceOpponents::Champion),
+            "population" => Ok(RaceOpponents::Population),
+            _ => anyhow::bail!("unknown race opponents {}", s),
+        }
+    }
+}
+
 pub struct GameState {
     pub world: World,
     dispatcher: Box<dyn UnifiedDispatcher + 'static>,
     stage: Stage,
+    mode: GameMode,
+    options: GameOptions,
+    stats_log: Option<StatsLog>,
+    /// The round played back in `GameMode::Replay`.
+    replay: Option<ReplayFile>,
+    /// Hall of fame entry shown on the HUD, while the hall of fame is open.
+    #[cfg(feature = "render")]
+    hall_of_fame_view: Option<usize>,
 }
 
 impl Default for GameState {
     fn default() -> Self {
+        GameState::new(GameOptions::default())
+    }
+}
+
+impl GameState {
+    pub fn new(options: GameOptions) -> Self {
         GameState {
             world: World::new(),
             dispatcher: system::build(),
             stage: Stage::Ready,
+            mode: GameMode::Training,
+            options,
+            stats_log: None,
+            replay: None,
+            #[cfg(feature = "render")]
+            hall_of_fame_view: None,
         }
     }
-}
 
-impl GameState {
-    pub fn init(&mut self) {
+    pub fn init(&mut self) -> anyhow::Result<()> {
+        self.setup_world();
+
+        let seed = self.options.seed.unwrap_or_else(sim_rng::random_seed);
+        log::info!("simulation seed {}", seed);
+        let shape = NetworkShape::new(self.options.sensors.clone(), &self.options.hidden_layers, NETWORK_OUTPUT_SIZE,
+                                      self.options.hidden_activation, self.options.output_activation);
+        let mut gene_handler = GeneHandler::new(seed, shape);
+        gene_handler.set_evolution(&self.options.config.evolution);
+        if self.options.config.evolution.genome == GenomeKind::Neat {
+            gene_handler.use_neat(self.options.hidden_activation);
+        }
+        self.world.insert(gene_handler);
+
+        if let Some(path) = self.options.play_champion.clone() {
+            let file = NetworkFile::load(&path)?;
+            self.world.insert(GeneHandler::from_network_file(&file, seed)?);
+            self.mode = GameMode::Champion;
+            log::info!("playing champion from {} (generation {}, fitness {:.3})", path.display(), file.generation, file.fitness);
+        }
+
+        self.init_game();
+
+        if let Some(path) = self.options.stats_path.clone() {
+            self.stats_log = Some(StatsLog::open(&path, self.options.resume.is_some())?);
+            log::info!("writing generation statistics to {}", path.display());
+        }
+        if let Some(dir) = &self.options.record_dir {
+            std::fs::create_dir_all(dir)?;
+            log::info!("recording replays to {}", dir.display());
+        }
+
+        if let Some(path) = self.options.resume.clone() {
+            self.load_population(&path)?;
+            log::info!("resumed population from {}", path.display());
+        }
+
+        if let Some(path) = self.options.replay.clone() {
+            let file = ReplayFile::load(&path)?;
+            log::info!("replaying generation {} of seed {} from {}", file.generation, file.seed, path.display());
+            // the round only plays out the same under the physics and tick rate it was recorded with
+            self.world.insert(file.config.clone());
+            self.options.tick_rate = file.tick_rate;
+            self.world.write_resource::<ReplayLog>().birds = file.birds.clone();
+            self.world.write_resource::<ReplayLog>().playing = true;
+            self.replay = Some(file);
+            self.mode = GameMode::Replay;
+            self.init_game();
+        } else if let Some(opponents) = self.options.race {
+            self.start_race(opponents)?;
+        } else if self.options.human {
+            if self.mode == GameMode::Champion {
+                anyhow::bail!("--human can't be combined with --play-champion, use --race champion");
+            }
+            self.mode = GameMode::Human;
+            self.init_game();
+        }
+        Ok(())
+    }
+
+    /// Switches to a race against `opponents`, after the population has been loaded.
+    fn start_race(&mut self, opponents: RaceOpponents) -> anyhow::Result<()> {
+        if opponents == RaceOpponents::Champion && self.mode != GameMode::Champion {
+            let champion = {
+                let gene_handler = self.world.read_resource::<GeneHandler>();
+                if gene_handler.is_neat() {
+                    anyhow::bail!("NEAT champions can't race, use --race population");
+                }
+                // a resumed population that wasn't scored yet still has its hall of fame
+                let genes = gene_handler.best_genome().map(|champion| champion.genes)
+                    .or_else(|| gene_handler.hall_of_fame().entries.first().map(|entry| entry.genes.clone()))
+                    .ok_or_else(|| anyhow::anyhow!("no champion to race, use --resume or --play-champion"))?;
+                GeneHandler::from_genome(genes, gene_handler.shape().clone(), gene_handler.generation, gene_handler.seed())
+            };
+            self.world.insert(champion);
+        }
+        self.mode = GameMode::Race(opponents);
+        self.init_game();
+        Ok(())
+    }
+
+    fn setup_world(&mut self) {
         self.world.register::<Transform>();
         self.world.register::<Collider>();
         self.world.register::<Tile>();
@@ -52,31 +192,104 @@ impl GameState {
         self.world.register::<Animation>();
         self.world.register::<Text>();
         self.world.register::<DNA>();
+        self.world.register::<PreviousPosition>();
+        self.world.register::<Tint>();
+        self.world.register::<BirdScore>();
+        self.world.register::<Human>();
+        self.world.register::<ReplaySlot>();
 
-        self.world.insert(Camera::init_orthographic(9));
+        self.world.insert(Camera::init_orthographic(9, self.options.config.screen_size));
+        self.world.insert(self.options.config.clone());
         self.world.insert(DeltaTime(0.05));
         self.world.insert(GameFinished(false));
-        self.world.insert(ThreadRng::default());
+        self.world.insert(CourseRng::default());
         self.world.insert(Score::default());
         self.world.insert(InputHandler::default());
-        self.world.insert(GeneHandler::default());
+        self.world.insert(HumanScore::default());
+        self.world.insert(ReplayLog::default());
+        self.world.insert(TimeScale::default());
+    }
 
+    /// Scores the genomes of `genes` on one course in a world of their own, until every bird
+    /// is dead or `max_time` has passed. `Evaluator` runs this on its worker threads.
+    pub fn evaluate_course(options: GameOptions, genes: GeneHandler, course_rng: CourseRng, max_time: Option<f32>) -> Evaluation {
+        let delta_time = 1.0 / options.tick_rate as f32;
+        let mut gs = GameState::new(options);
+        gs.setup_world();
+        gs.world.insert(genes);
+        gs.spawn_round(course_rng);
+        gs.start_run();
 
-        self.init_game();
+        while gs.stage == Stage::Run {
+            if max_time.is_some_and(|max| gs.elapsed_score() >= max) {
+                gs.finish_generation();
+            } else {
+                gs.run_systems(delta_time);
+            }
+            gs.check_game_finished();
+        }
+        let gene_handler = gs.world.read_resource::<GeneHandler>();
+        Evaluation { fitness: gene_handler.fitness().to_vec(), terms: gene_handler.fitness_terms().to_vec() }
+    }
+
+    /// Scores the whole population with `evaluator` instead of playing the round in this world,
+    /// then moves on to the next generation.
+    pub fn evaluate_generation(&mut self, evaluator: &Evaluator, max_time: Option<f32>) {
+        let evaluation = evaluator.evaluate(&self.world.read_resource::<GeneHandler>(), &self.options, max_time);
+        {
+            let mut gene_handler = self.world.write_resource::<GeneHandler>();
+            for (index, (score, terms)) in evaluation.fitness.into_iter().zip(evaluation.terms).enumerate() {
+                gene_handler.set_score(index, score);
+                gene_handler.set_terms(index, terms);
+            }
+        }
+        self.end_round();
     }
 
     fn init_game(&mut self) {
+        let course_rng = match &self.replay {
+            Some(replay) => CourseRng::for_round(replay.seed, replay.generation),
+            None => {
+                let gene_handler = self.world.read_resource::<GeneHandler>();
+                CourseRng::for_round(gene_handler.seed(), gene_handler.generation)
+            }
+        };
+        self.spawn_round(course_rng);
+    }
+
+    /// Builds a fresh round for the current population on the course drawn from `course_rng`.
+    fn spawn_round(&mut self, course_rng: CourseRng) {
         self.world.delete_all();
+
+        self.world.insert(course_rng);
+        let shape = self.world.read_resource::<GeneHandler>().shape().clone();
+        self.world.insert(shape);
+
         background(&mut self.world);
 
 
         pipe(&mut self.world, 16.);
         pipe(&mut self.world, 8.);
 
-        for _ in 0..100 {
-            ai_player(&mut self.world);
+        if let Some(replay) = &self.replay {
+            let humans: Vec<bool> = replay.birds.iter().map(|bird| bird.genome.is_none()).collect();
+            for (slot, human) in humans.into_iter().enumerate() {
+                replay_player(&mut self.world, slot, human);
+            }
+        } else {
+            if self.mode != GameMode::Human {
+                let gene_count = self.world.read_resource::<GeneHandler>().gene_count();
+                for _ in 0..gene_count {
+                    ai_player(&mut self.world);
+                }
+            }
+            // spawned last so it's drawn on top of the network birds
+            if self.has_human() {
+                player(&mut self.world);
+            }
+            self.start_recording();
         }
-        // player(&mut self.world);
+        self.world.write_resource::<ReplayLog>().tick = 0;
 
 
         let mut finished = self.world.write_resource::<GameFinished>();
@@ -87,7 +300,6 @@ impl GameState {
 
         let mut score = self.world.write_resource::<Score>();
         *score = Score::default();
-
         self.stage = Stage::Ready;
     }
 
@@ -106,9 +318,16 @@ impl GameState {
     pub fn update(&mut self, dt: f32) {
         self.check_game_finished();
 
-        if self.stage == Stage::End {
-            self.world.write_resource::<GeneHandler>().process_generation();
-            self.init_game();
+        if self.stage == Stage::Run && matches!(self.mode, GameMode::Race(_)) && self.race_standing().is_decided() {
+            self.finish_race();
+        }
+        if self.stage == Stage::Run && self.replay.as_ref().is_some_and(|replay| self.world.read_resource::<ReplayLog>().tick >= replay.ticks) {
+            self.stage = Stage::End;
+        }
+
+        // a person restarts on their own, see `restart_round`
+        if self.stage == Stage::End && !self.restarts_by_hand() {
+            self.end_round();
             self.stage = Stage::Run;
         }
 
@@ -116,17 +335,211 @@ impl GameState {
             return;
         }
 
+        self.run_systems(dt);
+    }
+
+    /// Moves the scored population on to the next round.
+    fn end_round(&mut self) {
+        self.save_replay();
+        match self.mode {
+            GameMode::Training => {
+                self.autosave();
+                self.world.write_resource::<GeneHandler>().process_generation();
+            }
+            GameMode::Champion => {
+                self.world.write_resource::<GeneHandler>().replay_generation();
+            }
+            GameMode::Human | GameMode::Race(_) | GameMode::Replay => {}
+        }
+        self.log_stats();
+        self.init_game();
+    }
+
+    fn has_human(&self) -> bool {
+        matches!(self.mode, GameMode::Human | GameMode::Race(_))
+    }
+
+    /// The round waits at its end until a person starts the next one.
+    fn restarts_by_hand(&self) -> bool {
+        self.has_human() || self.mode == GameMode::Replay
+    }
+
+    /// Gives every bird of a new round its place in the `ReplayLog`.
+    fn start_recording(&mut self) {
+        let birds: Vec<(Entity, Option<usize>)> = (&self.world.entities(), &self.world.read_storage::<Player>(), self.world.read_storage::<DNA>().maybe())
+            .join()
+            .map(|(entity, _, dna)| (entity, dna.map(|dna| dna.index)))
+            .collect();
+        let mut slots = self.world.write_storage::<ReplaySlot>();
+        for (slot, (entity, _)) in birds.iter().enumerate() {
+            slots.insert(*entity, ReplaySlot(slot)).expect("bird was just spawned");
+        }
+        self.world.write_resource::<ReplayLog>().birds = birds.into_iter()
+            .map(|(_, genome)| BirdRecord { genome, ..BirdRecord::default() })
+            .collect();
+    }
+
+    /// Saves the round that just ended to the `--record` directory.
+    fn save_replay(&self) {
+        let Some(dir) = &self.options.record_dir else {
+            return;
+        };
+        let log = self.world.read_resource::<ReplayLog>();
+        if log.playing || log.tick == 0 {
+            return;
+        }
+        let gene_handler = self.world.read_resource::<GeneHandler>();
+        let file = ReplayFile {
+            version: REPLAY_FILE_VERSION,
+            seed: gene_handler.seed(),
+            generation: gene_handler.generation,
+            tick_rate: self.options.tick_rate,
+            ticks: log.tick,
+            config: (*self.world.read_resource::<GameConfig>()).clone(),
+            birds: log.birds.iter()
+                .map(|bird| BirdRecord { fitness: bird.genome.map_or(0.0, |genome| gene_handler.fitness()[genome].max(0.0)), ..bird.clone() })
+                .collect(),
+        };
+        if let Err(e) = file.save_new(dir) {
+            log::error!("saving replay to {} failed: {}", dir.display(), e);
+        }
+    }
+
+    /// Starts the next round once a person asks for it.
+    #[cfg(feature = "render")]
+    fn restart_round(&mut self) {
+        self.save_replay();
+        if matches!(self.mode, GameMode::Race(_)) {
+            // the opponents fly again as they are
+            self.world.write_resource::<GeneHandler>().clear_scores();
+        }
+        self.init_game();
+    }
+
+    pub fn race_standing(&self) -> RaceStanding {
+        let scores = self.world.read_storage::<BirdScore>();
+        let human = (&self.world.read_storage::<Human>(), &scores).join().map(|(_, score)| *score).next();
+        let last_human = self.world.read_resource::<HumanScore>().last;
+        let flying: Vec<u32> = (&self.world.read_storage::<DNA>(), &scores).join().map(|(_, score)| score.pipes).collect();
+        let crashed = self.world.read_resource::<GeneHandler>().fitness_terms().iter()
+            .map(|terms| terms.pipes as u32)
+            .max()
+            .unwrap_or(0);
+        RaceStanding {
+            human_pipes: human.or(last_human).map_or(0, |score| score.pipes),
+            human_alive: human.is_some(),
+            ai_pipes: flying.iter().copied().max().unwrap_or(0).max(crashed),
+            ai_alive: flying.len(),
+        }
+    }
 
+    /// Stops the round as soon as the winner is known, a champion could fly on for minutes.
+    fn finish_race(&mut self) {
+        let human = (&self.world.read_storage::<Human>(), &self.world.read_storage::<BirdScore>())
+            .join()
+            .map(|(_, score)| *score)
+            .next();
+        if let Some(score) = human {
+            self.world.write_resource::<HumanScore>().record(score);
+        }
+        self.stage = Stage::End;
+    }
+
+    fn run_systems(&mut self, dt: f32) {
         self.update_delta_time(dt);
         self.dispatcher.run_now(&mut self.world);
         self.world.maintain();
     }
 
+    /// Advances exactly one tick while paused.
+    pub fn step_paused(&mut self) {
+        if self.stage != Stage::Pause {
+            return;
+        }
+        self.snapshot_positions();
+        self.run_systems(1.0 / self.options.tick_rate as f32);
+    }
+
+    pub fn time_scale(&self) -> TimeScale {
+        *self.world.read_resource::<TimeScale>()
+    }
+
+    pub fn set_time_scale(&mut self, time_scale: TimeScale) {
+        *self.world.write_resource::<TimeScale>() = time_scale;
+    }
+
+    #[cfg(feature = "render")]
     pub fn handle_keyboard_input(&mut self, physical_key: PhysicalKey, state: ElementState) -> bool {
+        match physical_key {
+            PhysicalKey::Code(KeyCode::F5) => {
+                if state == ElementState::Released {
+                    match self.save_population() {
+                        Ok(_) => log::info!("saved population to {}", self.options.checkpoint_path.display()),
+                        Err(e) => log::error!("failed to save population: {}", e),
+                    }
+                }
+                return true;
+            }
+            PhysicalKey::Code(KeyCode::F6) => {
+                if state == ElementState::Released {
+                    match self.export_champion() {
+                        Ok(_) => log::info!("exported champion to {}", self.options.champion_path.display()),
+                        Err(e) => log::error!("failed to export champion: {}", e),
+                    }
+                }
+                return true;
+            }
+            PhysicalKey::Code(KeyCode::F9) => {
+                if state == ElementState::Released {
+                    let path = self.options.checkpoint_path.clone();
+                    match self.load_population(&path) {
+                        Ok(_) => log::info!("loaded population from {}", path.display()),
+                        Err(e) => log::error!("failed to load population: {}", e),
+                    }
+                }
+                return true;
+            }
+            PhysicalKey::Code(KeyCode::KeyH) => {
+                if state == ElementState::Released {
+                    self.hall_of_fame_view = match self.hall_of_fame_view {
+                        Some(_) => None,
+                        None => Some(0),
+                    };
+                }
+                return true;
+            }
+            PhysicalKey::Code(code @ (KeyCode::ArrowUp | KeyCode::ArrowDown | KeyCode::KeyI)) if self.hall_of_fame_view.is_some() => {
+                if state == ElementState::Released {
+                    self.browse_hall_of_fame(code);
+                }
+                return true;
+            }
+            // a person flies at the speed the game is played at
+            PhysicalKey::Code(code @ (KeyCode::Digit1 | KeyCode::Digit2 | KeyCode::Digit3 | KeyCode::Digit4 | KeyCode::Digit5)) if !self.has_human() => {
+                if state == ElementState::Released {
+                    self.set_time_scale(match code {
+                        KeyCode::Digit1 => TimeScale::X1,
+                        KeyCode::Digit2 => TimeScale::X2,
+                        KeyCode::Digit3 => TimeScale::X8,
+                        KeyCode::Digit4 => TimeScale::X64,
+                        _ => TimeScale::Max,
+                    });
+                }
+                return true;
+            }
+            _ => {}
+        }
+
         match self.stage {
             Stage::End => {
                 if state == ElementState::Released {
-                    self.init_game();
+                    self.restart_round();
+                }
+                return true;
+            }
+            Stage::Pause if physical_key == PhysicalKey::Code(KeyCode::KeyN) => {
+                if state == ElementState::Released {
+                    self.step_paused();
                 }
                 return true;
             }
@@ -162,24 +575,102 @@ impl GameState {
         }
     }
 
+    /// A mouse click or touch, handled like the jump key.
+    #[cfg(feature = "render")]
+    pub fn handle_pointer_input(&mut self, state: ElementState) -> bool {
+        match self.stage {
+            Stage::End => {
+                if state == ElementState::Released {
+                    self.restart_round();
+                }
+            }
+            Stage::Ready | Stage::Pause => {
+                if state == ElementState::Released {
+                    self.stage = Stage::Run;
+                }
+            }
+            Stage::Run => {
+                let mut input_handler = self.world.write_resource::<InputHandler>();
+                match state {
+                    ElementState::Pressed => input_handler.press(),
+                    ElementState::Released => input_handler.release(),
+                }
+            }
+        }
+        true
+    }
+
+    #[cfg(feature = "render")]
+    fn browse_hall_of_fame(&mut self, code: KeyCode) {
+        let Some(selected) = self.hall_of_fame_view else {
+            return;
+        };
+        let mut gene_handler = self.world.write_resource::<GeneHandler>();
+        let len = gene_handler.hall_of_fame().len();
+        if len == 0 {
+            return;
+        }
+        match code {
+            KeyCode::ArrowUp => self.hall_of_fame_view = Some((selected + len - 1) % len),
+            KeyCode::ArrowDown => self.hall_of_fame_view = Some((selected + 1) % len),
+            _ => match gene_handler.reintroduce(selected) {
+                Ok(_) => log::info!("hall of fame entry {} joins the next generation", selected + 1),
+                Err(e) => log::error!("failed to reintroduce: {}", e),
+            },
+        }
+    }
+
     pub fn get_camera_uniform(&self) -> [[f32; 4]; 4] {
         let camera = self.world.read_resource::<Camera>();
         let camera_uniform = camera.get_view_proj();
         return camera_uniform;
     }
 
-    pub fn get_tile_instance(&self) -> HashMap<String, Vec<TileRenderData>> {
+    /// Remembers where everything is before the next tick so rendering can interpolate.
+    pub fn snapshot_positions(&mut self) {
+        let entities = self.world.entities();
+        let transforms = self.world.read_storage::<Transform>();
+        let mut previous = self.world.write_storage::<PreviousPosition>();
+        for (e, transform) in (&entities, &transforms).join() {
+            previous.insert(e, PreviousPosition(transform.position)).expect("snapshot position fail!!!");
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.stage == Stage::Run
+    }
+
+    /// Ticks per second, those of the replay while one is played back.
+    pub fn tick_rate(&self) -> u32 {
+        self.options.tick_rate
+    }
+
+    /// `alpha` blends from the position before the last tick (0.0) to the current one (1.0).
+    #[cfg(feature = "render")]
+    pub fn get_tile_instance(&self, alpha: f32) -> HashMap<String, Vec<TileRenderData>> {
         let tiles = self.world.read_storage::<Tile>();
         let transforms = self.world.read_storage::<Transform>();
-        let rt_data = (&tiles, &transforms).join().collect::<Vec<_>>();
+        let previous = self.world.read_storage::<PreviousPosition>();
+        let tints = self.world.read_storage::<Tint>();
+        let rt_data = (&tiles, &transforms, previous.maybe(), tints.maybe()).join().collect::<Vec<_>>();
 
         let mut tile_instance_data_hashmap = HashMap::new();
-        for (tile, transform) in rt_data {
+        for (tile, transform, prev, tint) in rt_data {
             let atlas = tile.atlas.clone();
+            let mut position = transform.position;
+            if let Some(PreviousPosition(prev)) = prev {
+                // scrolled tiles jump back when they wrap around, don't smear them across the screen
+                if (position[0] - prev[0]).abs() < 1.0 && (position[1] - prev[1]).abs() < 1.0 {
+                    for i in 0..2 {
+                        position[i] = prev[i] + (position[i] - prev[i]) * alpha;
+                    }
+                }
+            }
             let instance = TileRenderData {
                 uv: tile.uv.clone(),
-                position: transform.position.clone(),
+                position,
                 size: transform.size.clone(),
+                tint: tint.map_or([1.0, 1.0, 1.0], |tint| tint.0),
             };
 
 
@@ -211,19 +702,147 @@ impl GameState {
     //
     //     text_render_data
     // }
+    /// The best fitness of the previous generation and the five best birds still flying,
+    /// with their genome index, lineage id and parent id.
+    #[cfg(feature = "render")]
+    fn leaderboard_text(&self, gene_handler: &GeneHandler) -> String {
+        let config = self.world.read_resource::<GameConfig>();
+        let transforms = self.world.read_storage::<Transform>();
+        let pipes = self.world.read_storage::<Pipe>();
+        let pipe_targets = self.world.read_storage::<PipeTarget>();
+        let next_pipe = Observation::gather((&pipes, &transforms, pipe_targets.maybe()).join()).pipes[0];
+
+        let mut leaders: Vec<(usize, FitnessTerms, f32)> = (&self.world.read_storage::<DNA>(), &self.world.read_storage::<BirdScore>(), &transforms)
+            .join()
+            .map(|(dna, score, transform)| {
+                let terms = FitnessTerms::of_bird(score, transform.position[1], &next_pipe);
+                (dna.index, terms, config.fitness.score(&terms))
+            })
+            .collect();
+        // birds alive since the start tie on time, the one closer to the gap goes first
+        leaders.sort_by(|a, b| b.2.total_cmp(&a.2).then(a.1.gap_distance.total_cmp(&b.1.gap_distance)));
+
+        let mut content = match gene_handler.previous_best {
+            Some(best) => format!("Last best:{:.3}", best),
+            None => "Last best:none".to_string(),
+        };
+        for (index, _, fitness) in leaders.iter().take(5) {
+            let lineage = gene_handler.lineage(*index);
+            let parent = lineage.parent.map_or("new".to_string(), |parent| format!("of:{}", parent));
+            content += &format!("\n{} id:{} {} {:.2}", index, lineage.id, parent, fitness);
+        }
+        content
+    }
+
+    /// The best recorded fitness and the five best birds of the replay still flying.
+    #[cfg(feature = "render")]
+    fn replay_text(&self) -> String {
+        let log = self.world.read_resource::<ReplayLog>();
+        let mut flying: Vec<&BirdRecord> = self.world.read_storage::<ReplaySlot>().join()
+            .filter_map(|slot| log.birds.get(slot.0))
+            .collect();
+        flying.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+
+        let best = log.birds.iter().map(|bird| bird.fitness).fold(0.0, f32::max);
+        let mut content = format!("Recorded best:{:.3}", best);
+        for bird in flying.iter().take(5) {
+            content += &match bird.genome {
+                Some(genome) => format!("\n{} {:.2}", genome, bird.fitness),
+                None => "\nHuman".to_string(),
+            };
+        }
+        content
+    }
+
+    /// Score of the person's bird while it flies, of their last round once it crashed.
+    #[cfg(feature = "render")]
+    fn human_text(&self) -> String {
+        let human_score = self.world.read_resource::<HumanScore>();
+        let live = (&self.world.read_storage::<Human>(), &self.world.read_storage::<BirdScore>())
+            .join()
+            .map(|(_, score)| *score)
+            .next();
+        let current = live.or(human_score.last).unwrap_or_default();
+        let mut content = format!("Time:{:.1}\nPipes:{}", current.time_alive, current.pipes);
+        if let Some(best) = human_score.best {
+            content += &format!("\nBest:{} pipes {:.1}", best.pipes, best.time_alive);
+        }
+        content
+    }
+
+    #[cfg(feature = "render")]
     pub fn set_score_text(&self) -> Vec<TextRenderData> {
         let gene_handler = self.world.read_resource::<GeneHandler>();
         let score = self.world.read_resource::<Score>();
         let players =  self.world.read_storage::<Player>().join().count();
+        let title = match self.mode {
+            GameMode::Training => "Generation",
+            GameMode::Champion => "Champion Round",
+            GameMode::Human => "Human",
+            GameMode::Race(_) => "Race",
+            GameMode::Replay => "Replay",
+        };
+        let time_scale = self.world.read_resource::<TimeScale>();
+        let mut content = match self.mode {
+            GameMode::Human => format!("{}:{}\n{}", title, gene_handler.generation, self.human_text()),
+            GameMode::Race(_) => {
+                let standing = self.race_standing();
+                format!("{}:{}\n{}\nAI:{} pipes  Flying:{}", title, gene_handler.generation, self.human_text(), standing.ai_pipes, standing.ai_alive)
+            }
+            GameMode::Replay => {
+                let ticks = self.replay.as_ref().map_or(0, |replay| replay.ticks);
+                let generation = self.replay.as_ref().map_or(0, |replay| replay.generation);
+                let tick = self.world.read_resource::<ReplayLog>().tick;
+                format!("{}:{}\nTick:{} of {}\nSurvive:{}\nSpeed:{}", title, generation, tick, ticks, players, time_scale.label())
+            }
+            _ => format!("{}:{}\nScore:{:.3}\nSurvive:{}\nSpeed:{}", title, gene_handler.generation, score.0, players, time_scale.label()),
+        };
+        if gene_handler.species_of(0).is_some() {
+            content += &format!("\nSpecies:{}", gene_handler.species_count());
+        }
+        // the hall of fame or the leaderboard goes below, one line is the font size plus the line space
+        let panel_y = 8.5 - content.lines().count() as f32 * 0.6 - 0.3;
         let mut text_render_data = vec![
             TextRenderData {
-                content: format!("Generation:{}\nScore:{:.3}\nSurvive:{}", gene_handler.generation, score.0, players),
+                content,
                 position: [-4.5, 8.5, 1.],
                 size: [0.5, 0.5],
                 color: [0.0, 0.0, 0.0],
             }
         ];
 
+        if let Some(selected) = self.hall_of_fame_view {
+            let hall_of_fame = gene_handler.hall_of_fame();
+            let content = match hall_of_fame.entries.get(selected) {
+                None => "Hall of fame is empty".to_string(),
+                Some(entry) => format!(
+                    "Hall of fame {} of {}\nGeneration:{}\nFitness:{:.3}\nSeed:{}\nI to reintroduce  Queued:{}",
+                    selected + 1, hall_of_fame.len(), entry.generation, entry.fitness, entry.seed,
+                    gene_handler.reintroduced_count()),
+            };
+            text_render_data.push(
+                TextRenderData {
+                    content,
+                    position: [-4.5, panel_y, 1.],
+                    size: [0.5, 0.5],
+                    color: [0.0, 0.0, 0.0],
+                }
+            );
+        } else {
+            let content = match self.mode {
+                GameMode::Replay => self.replay_text(),
+                _ => self.leaderboard_text(&gene_handler),
+            };
+            text_render_data.push(
+                TextRenderData {
+                    content,
+                    position: [-4.5, panel_y, 1.],
+                    size: [0.5, 0.5],
+                    color: [0.0, 0.0, 0.0],
+                }
+            );
+        }
+
         if self.stage == Stage::Ready {
             text_render_data.push(
                 TextRenderData {
@@ -235,47 +854,172 @@ impl GameState {
             );
         }
 
+        if self.stage == Stage::End && self.restarts_by_hand() {
+            let result = match self.mode {
+                GameMode::Replay => "Replay over".to_string(),
+                GameMode::Race(_) => {
+                    let standing = self.race_standing();
+                    format!("{}  You:{} AI:{}", standing.verdict(), standing.human_pipes, standing.ai_pipes)
+                }
+                _ => "Game over".to_string(),
+            };
+            text_render_data.push(
+                TextRenderData {
+                    content: format!("{}\nPress any key to restart", result),
+                    position: [-3., 1., 1.],
+                    size: [0.5, 0.5],
+                    color: [0.0, 0.0, 0.0],
+                }
+            );
+        }
+
+        if self.stage == Stage::Pause {
+            text_render_data.push(
+                TextRenderData {
+                    content: "Paused\nN to step  any key to resume".to_string(),
+                    position: [-3.5, 1., 1.],
+                    size: [0.5, 0.5],
+                    color: [0.0, 0.0, 0.0],
+                }
+            );
+        }
+
         text_render_data
     }
-    pub fn get_gene_data(&self) -> ([f32; GENE_SIZE], [f32; 2]) {
+    /// Layer sizes, genes and activated layer values of the last alive bird, for the network view.
+    /// Empty for NEAT populations, which have no layers to draw.
+    pub fn get_gene_data(&self) -> (Vec<usize>, Vec<f32>, Vec<Vec<f32>>) {
         let gene_handler = self.world.read_resource::<GeneHandler>();
+        if gene_handler.is_neat() {
+            return (vec![], vec![], vec![]);
+        }
 
 
         let player = self.world.read_storage::<Player>();
         let transform = self.world.read_storage::<Transform>();
         let dna = self.world.read_storage::<DNA>();
-        let pipe = self.world.read_storage::<PipeTarget>();
+        let pipe = self.world.read_storage::<Pipe>();
+        let pipe_target = self.world.read_storage::<PipeTarget>();
         let last_player = (&player, &transform, &dna).join().last();
 
-        let mut pipe_position = [99.0, 0.0];
-        for (_, pipe_tr) in (&pipe, &transform).join() {
-            if pipe_tr.position[0] > -3.0 &&  pipe_position[0] > pipe_tr.position[0] {
-                pipe_position = [pipe_tr.position[0], pipe_tr.position[1]];
-            }
-        }
-
-
+        let observation = Observation::gather((&pipe, &transform, pipe_target.maybe()).join());
 
-
-        let (position, index) = match last_player {
+        let (position, force, index) = match last_player {
             None => {
-                ([0.0, 0.0],0)
+                ([0.0, 0.0, 0.0], 0.0, 0)
             }
             Some(p) => {
-                ([p.1.position[0], p.1.position[1]],p.2.index)
+                (p.1.position, p.0.force, p.2.index)
             }
         };
 
-        let input_data = [
-            pipe_position[0] - position[0],
-            pipe_position[1] - position[1],
-        ];
+        let input_data = gene_handler.shape().sensors.read(&observation, position, force);
 
         let gene = gene_handler.get_alive_gene(index);
-        return (gene, input_data);
+        let values = process_network_layers(gene_handler.shape(), &gene, input_data);
+        return (gene_handler.shape().layers.clone(), gene, values);
     }
 
     pub fn force_restart(&mut self) {
         self.world.delete_all();
     }
+
+    pub fn save_population(&self) -> anyhow::Result<()> {
+        // outside training the GeneHandler holds the race opponents or the replayed genomes
+        if self.mode != GameMode::Training {
+            anyhow::bail!("populations can only be saved in training mode");
+        }
+        let gene_handler = self.world.read_resource::<GeneHandler>();
+        if gene_handler.is_neat() {
+            anyhow::bail!("NEAT populations can't be saved");
+        }
+        let file = gene_handler.to_population_file();
+        file.save(&self.options.checkpoint_path)
+    }
+
+    pub fn export_champion(&self) -> anyhow::Result<()> {
+        if self.mode != GameMode::Training {
+            anyhow::bail!("champions can only be exported in training mode");
+        }
+        let gene_handler = self.world.read_resource::<GeneHandler>();
+        if gene_handler.is_neat() {
+            anyhow::bail!("NEAT champions can't be exported");
+        }
+        let file = gene_handler.to_network_file()
+            .ok_or_else(|| anyhow::anyhow!("no genome has been scored yet"))?;
+        file.save(&self.options.champion_path)
+    }
+
+    pub fn load_population(&mut self, path: &Path) -> anyhow::Result<()> {
+        if self.mode != GameMode::Training {
+            anyhow::bail!("populations can only be loaded in training mode");
+        }
+        if self.world.read_resource::<GeneHandler>().is_neat() {
+            anyhow::bail!("populations can't be loaded into a NEAT run");
+        }
+        let file = PopulationFile::load(path)?;
+        self.world.write_resource::<GeneHandler>().restore(file)?;
+        self.init_game();
+        Ok(())
+    }
+
+    fn log_stats(&mut self) {
+        let Some(stats_log) = self.stats_log.as_mut() else {
+            return;
+        };
+        let Some(summary) = self.world.read_resource::<GeneHandler>().last_summary else {
+            return;
+        };
+        if let Err(e) = stats_log.append(&summary) {
+            log::error!("writing statistics failed: {}", e);
+        }
+    }
+
+    fn autosave(&self) {
+        let Some(interval) = self.options.autosave_interval else {
+            return;
+        };
+        let generation = self.world.read_resource::<GeneHandler>().generation;
+        if (generation + 1) % interval != 0 {
+            return;
+        }
+        if let Err(e) = self.save_population() {
+            log::error!("autosave failed: {}", e);
+        }
+    }
+
+    pub fn start_run(&mut self) {
+        self.stage = Stage::Run;
+    }
+
+    pub fn elapsed_score(&self) -> f32 {
+        self.world.read_resource::<Score>().0
+    }
+
+    pub fn take_generation_summary(&mut self) -> Option<GenerationSummary> {
+        self.world.write_resource::<GeneHandler>().last_summary.take()
+    }
+
+    /// Ends the current generation early, keeping the score of every bird still alive.
+    pub fn finish_generation(&mut self) {
+        {
+            let entities = self.world.entities();
+            let dna = self.world.read_storage::<DNA>();
+            let scores = self.world.read_storage::<BirdScore>();
+            let transforms = self.world.read_storage::<Transform>();
+            let pipes = self.world.read_storage::<Pipe>();
+            let pipe_targets = self.world.read_storage::<PipeTarget>();
+            let config = self.world.read_resource::<GameConfig>();
+            let mut gene_handler = self.world.write_resource::<GeneHandler>();
+            let next_pipe = Observation::gather((&pipes, &transforms, pipe_targets.maybe()).join()).pipes[0];
+            for (e, d, score, transform) in (&entities, &dna, &scores, &transforms).join() {
+                let terms = FitnessTerms::of_bird(score, transform.position[1], &next_pipe);
+                gene_handler.set_score(d.index, config.fitness.score(&terms));
+                gene_handler.set_terms(d.index, terms);
+                entities.delete(e).expect("delete player fail!!!");
+            }
+        }
+        self.world.maintain();
+        *self.world.write_resource::<GameFinished>() = GameFinished(true);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,105 @@
+use crate::evaluator::Evaluator;
+use crate::game_state::GameState;
+use crate::options::GameOptions;
+
+pub struct HeadlessOptions {
+    pub game: GameOptions,
+    pub generations: Option<usize>,
+    pub max_generation_time: Option<f32>,
+    pub export_champion: bool,
+    /// Scores generations over several courses in parallel instead of playing them in one world.
+    pub evaluator: Option<Evaluator>,
+}
+
+impl Default for HeadlessOptions {
+    fn default() -> Self {
+        HeadlessOptions {
+            game: GameOptions::default(),
+            generations: None,
+            max_generation_time: Some(300.0),
+            export_champion: false,
+            evaluator: None,
+        }
+    }
+}
+
+impl HeadlessOptions {
+    pub const USAGE: &'static str = "[--generations N] [--max-time SECONDS] [--export-champion] [--courses N] [--batch-size N] [--threads N]";
+
+    pub fn from_args<I: Iterator<Item=String>>(mut args: I) -> anyhow::Result<Self> {
+        let mut options = HeadlessOptions::default();
+        while let Some(arg) = args.next() {
+            if options.game.apply_arg(&arg, &mut args)? {
+                continue;
+            }
+            let mut value = || args.next().ok_or_else(|| anyhow::anyhow!("missing value for {}", arg));
+            match arg.as_str() {
+                "--generations" => options.generations = Some(positive(&value()?)?),
+                "--max-time" => {
+                    let max_time: f32 = value()?.parse()?;
+                    options.max_generation_time = if max_time > 0.0 { Some(max_time) } else { None };
+                }
+                "--export-champion" => options.export_champion = true,
+                "--courses" => options.evaluator.get_or_insert_with(Evaluator::default).courses = positive(&value()?)?,
+                "--batch-size" => options.evaluator.get_or_insert_with(Evaluator::default).batch_size = value()?.parse()?,
+                "--threads" => options.evaluator.get_or_insert_with(Evaluator::default).threads = positive(&value()?)?,
+                _ => anyhow::bail!("unknown argument {}", arg),
+            }
+        }
+        if options.game.human || options.game.race.is_some() || options.game.replay.is_some() {
+            anyhow::bail!("--human, --race and --replay need a window");
+        }
+        if options.evaluator.is_some() && options.game.record_dir.is_some() {
+            // evaluated rounds are played in worlds of their own, on several courses
+            anyhow::bail!("--record can't be combined with --courses, --batch-size or --threads");
+        }
+        Ok(options)
+    }
+}
+
+/// Runs generations back to back without a window, printing one line per finished generation.
+pub fn run(options: HeadlessOptions) -> anyhow::Result<()> {
+    let delta_time = 1.0 / options.game.tick_rate as f32;
+    let mut gs = GameState::new(options.game);
+    gs.init()?;
+    gs.start_run();
+
+    loop {
+        match &options.evaluator {
+            Some(evaluator) => gs.evaluate_generation(evaluator, options.max_generation_time),
+            None => gs.update(delta_time),
+        }
+
+        if let Some(summary) = gs.take_generation_summary() {
+            println!(
+                "generation {:>5}  best {:>10.3}  average {:>10.3}  best_index {:>3}  species {:>3}  elapsed {:>7.3}s",
+                summary.generation,
+                summary.best_fitness,
+                summary.average_fitness,
+                summary.best_index,
+                summary.species,
+                summary.wall_time
+            );
+
+            if options.generations.is_some_and(|max| summary.generation + 1 >= max) {
+                break;
+            }
+        }
+
+        if options.evaluator.is_none() && options.max_generation_time.is_some_and(|max| gs.elapsed_score() >= max) {
+            gs.finish_generation();
+        }
+    }
+
+    if options.export_champion {
+        gs.export_champion()?;
+    }
+    Ok(())
+}
+
+fn positive(value: &str) -> anyhow::Result<usize> {
+    match value.parse()? {
+        0 => anyhow::bail!("expected a positive number, got 0"),
+        n => Ok(n),
+    }
+}
@@ -1,27 +1,42 @@
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
+#[cfg(feature = "render")]
 use crate::application::Application;
+#[cfg(feature = "render")]
 use crate::winit_state::WinitState;
 
+#[cfg(feature = "render")]
 mod renderer;
+#[cfg(feature = "render")]
 pub mod winit_state;
+#[cfg(feature = "render")]
 pub mod application;
-mod components;
-mod resources;
-mod system;
-mod game_state;
-mod builder;
-mod game_configs;
+pub mod components;
+pub mod resources;
+pub mod system;
+pub mod game_state;
+pub mod builder;
+pub mod game_configs;
+pub mod headless;
+pub mod evaluator;
+pub mod fixed_timestep;
+pub mod options;
 
 #[cfg(target_arch = "wasm32")]
 mod wasm_bindings;
 
+#[cfg(feature = "render")]
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen(start))]
 pub async fn start(){
+    start_with(options::GameOptions::default()).await;
+}
+
+#[cfg(feature = "render")]
+pub async fn start_with(options: options::GameOptions){
     let title = "wgpu_wasm";
-    let width = game_configs::SCREEN_SIZE[0];
-    let height = game_configs::SCREEN_SIZE[1];
+    let width = options.config.screen_size[0];
+    let height = options.config.screen_size[1];
 
     cfg_if::cfg_if! {
         if #[cfg(target_arch = "wasm32")] {
@@ -34,7 +49,7 @@ pub async fn start(){
 
     let (wb, event_loop) = WinitState::create(title, width, height );
     // let asset_path = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/").to_string();
-    let mut application = Application::new(wb, &event_loop).await;
+    let mut application = Application::new(wb, &event_loop, options).await;
     event_loop.run_app(&mut application).unwrap();
 
 
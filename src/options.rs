@@ -0,0 +1,139 @@
+use std::path::{Path, PathBuf};
+
+use crate::components::Activation;
+use crate::game_state::RaceOpponents;
+use crate::game_configs::{HIDDEN_ACTIVATION, HIDDEN_LAYERS, OUTPUT_ACTIVATION};
+use crate::resources::{GameConfig, ReplayFile, SensorSet};
+
+/// Startup options shared by the window and headless binaries.
+#[derive(Clone)]
+pub struct GameOptions {
+    pub checkpoint_path: PathBuf,
+    pub resume: Option<PathBuf>,
+    pub autosave_interval: Option<usize>,
+    pub champion_path: PathBuf,
+    pub play_champion: Option<PathBuf>,
+    /// A person flies the bird instead of the population, see `GameMode::Human`.
+    pub human: bool,
+    /// A person races the birds of `GameMode::Race` on the same course.
+    pub race: Option<RaceOpponents>,
+    /// Every finished round is saved as a replay file in this directory.
+    pub record_dir: Option<PathBuf>,
+    /// Replay file to play back instead of running the population.
+    pub replay: Option<PathBuf>,
+    /// Per-generation statistics are appended here, as CSV or JSON Lines.
+    pub stats_path: Option<PathBuf>,
+    /// Drives gene initialization, evolution and pipe layouts. Random when not given.
+    pub seed: Option<u64>,
+    /// Simulation ticks per second, independent of the frame rate.
+    pub tick_rate: u32,
+    /// Physics and course settings, from `--config` and `--set` in command line order.
+    pub config: GameConfig,
+    /// Hidden layer sizes of new populations. Loaded populations and networks keep their own shape.
+    pub hidden_layers: Vec<usize>,
+    /// Activations of new populations, like `hidden_layers`.
+    pub hidden_activation: Activation,
+    pub output_activation: Activation,
+    /// Network inputs of new populations, like `hidden_layers`.
+    pub sensors: SensorSet,
+}
+
+impl Default for GameOptions {
+    fn default() -> Self {
+        GameOptions {
+            checkpoint_path: PathBuf::from("population.json"),
+            resume: None,
+            autosave_interval: None,
+            champion_path: PathBuf::from("champion.json"),
+            play_champion: None,
+            human: false,
+            race: None,
+            record_dir: None,
+            replay: None,
+            stats_path: None,
+            seed: None,
+            tick_rate: 60,
+            config: GameConfig::default(),
+            hidden_layers: HIDDEN_LAYERS.to_vec(),
+            hidden_activation: HIDDEN_ACTIVATION,
+            output_activation: OUTPUT_ACTIVATION,
+            sensors: SensorSet::default(),
+        }
+    }
+}
+
+impl GameOptions {
+    pub const USAGE: &'static str = "[--checkpoint PATH] [--resume PATH] [--autosave GENERATIONS] [--champion PATH] [--play-champion PATH] [--human] [--race champion|population] [--record DIR] [--replay PATH] [--stats PATH] [--seed N] [--tick-rate HZ] [--config PATH] [--set KEY=VALUE] [--hidden-layers N,N,...] [--hidden-activation NAME] [--output-activation NAME] [--sensors NAME,NAME,...] [--normalize none|scale|tanh] [--rays ANGLE,ANGLE,...] [--genome fixed|neat] [--optimizer ga|cma_es|nes]";
+
+    pub fn from_args<I: Iterator<Item=String>>(mut args: I) -> anyhow::Result<Self> {
+        let mut options = GameOptions::default();
+        while let Some(arg) = args.next() {
+            if !options.apply_arg(&arg, &mut args)? {
+                anyhow::bail!("unknown argument {}", arg);
+            }
+        }
+        if let Some(path) = &options.replay {
+            // the window opens at the size the round was recorded in, `GameState::init` applies the rest
+            options.config = ReplayFile::load(path)?.config;
+        }
+        Ok(options)
+    }
+
+    /// Applies one command line flag, taking its value from `args`.
+    /// Returns `false` if the flag is not a game option.
+    pub fn apply_arg<I: Iterator<Item=String>>(&mut self, arg: &str, args: &mut I) -> anyhow::Result<bool> {
+        let mut value = || args.next().ok_or_else(|| anyhow::anyhow!("missing value for {}", arg));
+        match arg {
+            "--checkpoint" => self.checkpoint_path = PathBuf::from(value()?),
+            "--resume" => self.resume = Some(PathBuf::from(value()?)),
+            "--autosave" => {
+                let interval: usize = value()?.parse()?;
+                self.autosave_interval = if interval > 0 { Some(interval) } else { None };
+            }
+            "--champion" => self.champion_path = PathBuf::from(value()?),
+            "--play-champion" => self.play_champion = Some(PathBuf::from(value()?)),
+            "--human" => self.human = true,
+            "--race" => self.race = Some(value()?.parse()?),
+            "--record" => self.record_dir = Some(PathBuf::from(value()?)),
+            "--replay" => self.replay = Some(PathBuf::from(value()?)),
+            "--stats" => self.stats_path = Some(PathBuf::from(value()?)),
+            "--seed" => self.seed = Some(value()?.parse()?),
+            "--tick-rate" => {
+                let tick_rate: u32 = value()?.parse()?;
+                if tick_rate == 0 {
+                    anyhow::bail!("--tick-rate must be positive");
+                }
+                self.tick_rate = tick_rate;
+            }
+            "--config" => self.config = GameConfig::load(Path::new(&value()?))?,
+            "--set" => self.config.apply_override(&value()?)?,
+            "--hidden-layers" => {
+                let hidden_layers = value()?.split(',')
+                    .map(str::trim)
+                    .filter(|size| !size.is_empty())
+                    .map(|size| size.parse::<usize>())
+                    .collect::<Result<Vec<_>, _>>()?;
+                if hidden_layers.contains(&0) {
+                    anyhow::bail!("hidden layers can't be empty");
+                }
+                self.hidden_layers = hidden_layers;
+            }
+            "--hidden-activation" => self.hidden_activation = value()?.parse()?,
+            "--output-activation" => self.output_activation = value()?.parse()?,
+            "--sensors" => self.sensors.sensors = SensorSet::parse_sensors(&value()?)?,
+            "--normalize" => self.sensors.normalization = value()?.parse()?,
+            "--genome" => self.config.evolution.genome = value()?.parse()?,
+            "--optimizer" => self.config.evolution.optimizer = value()?.parse()?,
+            "--rays" => {
+                let angles = value()?.split(',')
+                    .map(str::trim)
+                    .filter(|angle| !angle.is_empty())
+                    .map(|angle| angle.parse::<i16>())
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.sensors.set_rays(&angles);
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
@@ -30,6 +30,7 @@ impl std::fmt::Debug for Mesh {
 pub struct InstanceTileRaw {
     pub(crate) uv: [f32; 4],
     pub(crate) model: [[f32; 4]; 4],
+    pub(crate) tint: [f32; 3],
 }
 
 impl InstanceTileRaw {
@@ -64,6 +65,11 @@ impl InstanceTileRaw {
                     shader_location: 8,
                     format: wgpu::VertexFormat::Float32x4,
                 },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 20]>() as wgpu::BufferAddress,
+                    shader_location: 9,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
             ],
         }
     }
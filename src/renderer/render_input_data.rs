@@ -11,6 +11,8 @@ pub struct TileRenderData {
     pub uv: [f32; 4],
     pub position: [f32; 3],
     pub size: [f32; 2],
+    /// Multiplies the texture color, white leaves the tile unchanged.
+    pub tint: [f32; 3],
 }
 
 impl TileRenderData {
@@ -23,6 +25,7 @@ impl TileRenderData {
         InstanceTileRaw {
             uv: self.uv,
             model,
+            tint: self.tint,
         }
 
     }
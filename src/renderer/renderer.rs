@@ -34,10 +34,10 @@ pub struct RenderState {
     viewport_data: [f32; 6],
 }
 impl RenderState {
-    pub async fn new(window: Arc<Window>) -> Self {
+    pub async fn new(window: Arc<Window>, screen_size: [u32; 2]) -> Self {
         // Use game config size instead of window inner_size to avoid WASM initialization issues
-        let width = crate::game_configs::SCREEN_SIZE[0];
-        let height = crate::game_configs::SCREEN_SIZE[1];
+        let width = screen_size[0];
+        let height = screen_size[1];
 
         // The instance is a handle to our GPU
         // BackendBit::PRIMARY => Vulkan + Metal + DX12 + Browser WebGPU
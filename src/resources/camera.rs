@@ -1,5 +1,4 @@
 use cgmath::Point3;
-use crate::game_configs::SCREEN_SIZE;
 
 pub struct Camera {
     eye: Point3<f32>,
@@ -68,9 +67,9 @@ impl Camera {
             }
         }
 
-    pub fn init_orthographic( height: u32) -> Self {
+    pub fn init_orthographic( height: u32, screen_size: [u32;2]) -> Self {
         let height = height as f32;
-        let width = SCREEN_SIZE[0] as f32 / SCREEN_SIZE[1] as f32 * height;
+        let width = screen_size[0] as f32 / screen_size[1] as f32 * height;
         Self {
             // position the camera one unit up and 2 units back
             // +z is out of the screen
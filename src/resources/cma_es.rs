@@ -0,0 +1,244 @@
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+
+use crate::resources::operators::standard_normal;
+use crate::resources::optimizer::{ranked_best_first, recombination_weights, best_genome, Optimizer, OptimizerState};
+use crate::resources::NetworkShape;
+
+/// Covariance matrix adaptation evolution strategy (Hansen's (μ/μ_w, λ)-CMA-ES).
+///
+/// The population is λ samples of a normal distribution around `mean`; every generation the mean
+/// moves to the weighted best half, and the covariance and step size follow the successful steps.
+/// The first scored population, usually uniform random genomes, only picks the starting mean.
+pub struct CmaEs {
+    initial_sigma: f64,
+    state: Option<CmaState>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CmaState {
+    mean: Vec<f64>,
+    sigma: f64,
+    covariance: Vec<Vec<f64>>,
+    /// Eigenvectors of the covariance as columns, and the square roots of its eigenvalues.
+    basis: Vec<Vec<f64>>,
+    scales: Vec<f64>,
+    path_c: Vec<f64>,
+    path_sigma: Vec<f64>,
+    generation: usize,
+}
+
+impl CmaEs {
+    pub fn new(sigma: f32) -> Self {
+        CmaEs { initial_sigma: sigma as f64, state: None }
+    }
+}
+
+impl Optimizer for CmaEs {
+    fn start(&mut self, _: &[Vec<f32>], _: &mut StdRng) {
+        self.state = None;
+    }
+
+    fn state(&self) -> Option<OptimizerState> {
+        self.state.clone().map(OptimizerState::CmaEs)
+    }
+
+    fn resume(&mut self, state: OptimizerState) {
+        if let OptimizerState::CmaEs(state) = state {
+            self.state = Some(state);
+        }
+    }
+
+    fn next_generation(&mut self, population: &[Vec<f32>], fitness: &[f32], _: &NetworkShape, rng: &mut StdRng) -> Vec<Vec<f32>> {
+        let n = population[0].len();
+        let lambda = population.len();
+        match self.state.as_mut() {
+            Some(state) => state.update(population, fitness),
+            None => {
+                self.state = Some(CmaState {
+                    mean: best_genome(population, fitness),
+                    sigma: self.initial_sigma,
+                    covariance: identity(n),
+                    basis: identity(n),
+                    scales: vec![1.0; n],
+                    path_c: vec![0.0; n],
+                    path_sigma: vec![0.0; n],
+                    generation: 0,
+                });
+            }
+        }
+        let state = self.state.as_ref().unwrap();
+        (0..lambda).map(|_| state.sample(rng)).collect()
+    }
+
+    fn mutation_rate(&self, _: &[f32]) -> f32 {
+        self.state.as_ref().map_or(self.initial_sigma, |state| state.sigma) as f32
+    }
+}
+
+impl CmaState {
+    pub fn gene_size(&self) -> usize {
+        self.mean.len()
+    }
+
+    fn sample(&self, rng: &mut StdRng) -> Vec<f32> {
+        let n = self.mean.len();
+        let z: Vec<f64> = (0..n).map(|i| standard_normal(rng) as f64 * self.scales[i]).collect();
+        (0..n)
+            .map(|row| {
+                let y: f64 = (0..n).map(|col| self.basis[row][col] * z[col]).sum();
+                (self.mean[row] + self.sigma * y) as f32
+            })
+            .collect()
+    }
+
+    fn update(&mut self, population: &[Vec<f32>], fitness: &[f32]) {
+        let n = self.mean.len();
+        let nf = n as f64;
+        let mu = (population.len() / 2).max(1);
+        let weights = recombination_weights(mu);
+        let mu_eff = 1.0 / weights.iter().map(|w| w * w).sum::<f64>();
+
+        let c_c = (4.0 + mu_eff / nf) / (nf + 4.0 + 2.0 * mu_eff / nf);
+        let c_sigma = (mu_eff + 2.0) / (nf + mu_eff + 5.0);
+        let c_1 = 2.0 / ((nf + 1.3).powi(2) + mu_eff);
+        let c_mu = (1.0 - c_1).min(2.0 * (mu_eff - 2.0 + 1.0 / mu_eff) / ((nf + 2.0).powi(2) + mu_eff));
+        let damping = 1.0 + 2.0 * (((mu_eff - 1.0) / (nf + 1.0)).sqrt() - 1.0).max(0.0) + c_sigma;
+        let chi_n = nf.sqrt() * (1.0 - 1.0 / (4.0 * nf) + 1.0 / (21.0 * nf * nf));
+
+        // steps of the best samples, in units of sigma
+        let steps: Vec<Vec<f64>> = ranked_best_first(fitness).iter().take(mu)
+            .map(|i| population[*i].iter().zip(&self.mean).map(|(x, m)| (*x as f64 - m) / self.sigma).collect())
+            .collect();
+        let mut step = vec![0.0f64; n];
+        for (weight, y) in weights.iter().zip(&steps) {
+            for (s, y) in step.iter_mut().zip(y) {
+                *s += weight * y;
+            }
+        }
+        for (m, s) in self.mean.iter_mut().zip(&step) {
+            *m += self.sigma * s;
+        }
+
+        // C^-1/2 * step = B D^-1 B^T step
+        let rotated: Vec<f64> = (0..n)
+            .map(|col| (0..n).map(|row| self.basis[row][col] * step[row]).sum::<f64>() / self.scales[col])
+            .collect();
+        let whitened: Vec<f64> = (0..n).map(|row| (0..n).map(|col| self.basis[row][col] * rotated[col]).sum()).collect();
+        let sigma_factor = (c_sigma * (2.0 - c_sigma) * mu_eff).sqrt();
+        for (p, w) in self.path_sigma.iter_mut().zip(&whitened) {
+            *p = (1.0 - c_sigma) * *p + sigma_factor * w;
+        }
+
+        self.generation += 1;
+        let path_sigma_norm = norm(&self.path_sigma);
+        let stalled = path_sigma_norm / (1.0 - (1.0 - c_sigma).powi(2 * self.generation as i32)).sqrt() / chi_n
+            >= 1.4 + 2.0 / (nf + 1.0);
+        let h_sigma = if stalled { 0.0 } else { 1.0 };
+        let c_factor = (c_c * (2.0 - c_c) * mu_eff).sqrt();
+        for (p, s) in self.path_c.iter_mut().zip(&step) {
+            *p = (1.0 - c_c) * *p + h_sigma * c_factor * s;
+        }
+
+        let keep = 1.0 - c_1 - c_mu + (1.0 - h_sigma) * c_1 * c_c * (2.0 - c_c);
+        for row in 0..n {
+            for col in 0..=row {
+                let rank_mu: f64 = weights.iter().zip(&steps).map(|(w, y)| w * y[row] * y[col]).sum();
+                let value = keep * self.covariance[row][col]
+                    + c_1 * self.path_c[row] * self.path_c[col]
+                    + c_mu * rank_mu;
+                self.covariance[row][col] = value;
+                self.covariance[col][row] = value;
+            }
+        }
+
+        self.sigma *= ((c_sigma / damping) * (path_sigma_norm / chi_n - 1.0)).exp();
+
+        let (eigenvalues, eigenvectors) = jacobi_eigen(&self.covariance);
+        self.scales = eigenvalues.iter().map(|v| v.max(1e-20).sqrt()).collect();
+        self.basis = eigenvectors;
+    }
+}
+
+fn identity(n: usize) -> Vec<Vec<f64>> {
+    (0..n).map(|row| (0..n).map(|col| if row == col { 1.0 } else { 0.0 }).collect()).collect()
+}
+
+fn norm(v: &[f64]) -> f64 {
+    v.iter().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+/// Eigenvalues and eigenvectors (as columns) of a symmetric matrix, by cyclic Jacobi rotations.
+fn jacobi_eigen(matrix: &[Vec<f64>]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let n = matrix.len();
+    let mut a = matrix.to_vec();
+    let mut v = identity(n);
+    for _ in 0..50 {
+        let off_diagonal: f64 = (0..n).flat_map(|p| (p + 1..n).map(move |q| (p, q))).map(|(p, q)| a[p][q] * a[p][q]).sum();
+        if off_diagonal < 1e-22 {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[p][q].abs() < 1e-30 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for row in a.iter_mut() {
+                    let (akp, akq) = (row[p], row[q]);
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                let (upper, lower) = a.split_at_mut(q);
+                for (apk, aqk) in upper[p].iter_mut().zip(lower[0].iter_mut()) {
+                    let (x, y) = (*apk, *aqk);
+                    *apk = c * x - s * y;
+                    *aqk = s * x + c * y;
+                }
+                for row in v.iter_mut() {
+                    let (vkp, vkq) = (row[p], row[q]);
+                    row[p] = c * vkp - s * vkq;
+                    row[q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+    ((0..n).map(|i| a[i][i]).collect(), v)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use super::*;
+
+    #[test]
+    fn test_jacobi_eigen() {
+        let matrix = vec![vec![4.0, 1.0, 0.0], vec![1.0, 3.0, 1.0], vec![0.0, 1.0, 2.0]];
+        let (values, vectors) = jacobi_eigen(&matrix);
+        for (i, value) in values.iter().enumerate() {
+            for row in 0..3 {
+                let product: f64 = (0..3).map(|col| matrix[row][col] * vectors[col][i]).sum();
+                assert!((product - value * vectors[row][i]).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cma_es_minimizes_sphere() {
+        let shape = NetworkShape::default();
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut population: Vec<Vec<f32>> = (0..20)
+            .map(|_| (0..5).map(|_| standard_normal(&mut rng) * 4.0 + 4.0).collect())
+            .collect();
+        let mut cma = CmaEs::new(2.0);
+        for _ in 0..150 {
+            let fitness: Vec<f32> = population.iter().map(|x| -x.iter().map(|g| g * g).sum::<f32>()).collect();
+            population = cma.next_generation(&population, &fitness, &shape, &mut rng);
+        }
+        let mean = &cma.state.as_ref().unwrap().mean;
+        assert!(norm(mean) < 1e-3, "mean {:?}", mean);
+    }
+}
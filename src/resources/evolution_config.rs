@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game_configs::{ELITE_RATIO, HALL_OF_FAME_SIZE, MIN_SPECIES_SIZE, SPECIES_THRESHOLD};
+use crate::resources::{Crossover, Mutation, MutationRate, NeatConfig, OptimizerKind, Selection};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GenomeKind {
+    /// Fixed-topology network, one weight per gene.
+    #[default]
+    Fixed,
+    /// NEAT genomes that grow nodes and connections.
+    Neat,
+}
+
+impl std::str::FromStr for GenomeKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fixed" => Ok(GenomeKind::Fixed),
+            "neat" => Ok(GenomeKind::Neat),
+            _ => anyhow::bail!("unknown genome {}", s),
+        }
+    }
+}
+
+/// Weight-space speciation of fixed genomes, the `[evolution.speciation]` table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpeciationConfig {
+    pub enabled: bool,
+    /// Genomes whose root mean square weight difference to a species' representative is below this join it.
+    pub threshold: f32,
+    /// Children every species gets at least, while the population has room.
+    pub min_species_size: usize,
+}
+
+impl Default for SpeciationConfig {
+    fn default() -> Self {
+        SpeciationConfig {
+            enabled: false,
+            threshold: SPECIES_THRESHOLD,
+            min_species_size: MIN_SPECIES_SIZE,
+        }
+    }
+}
+
+impl SpeciationConfig {
+    fn apply_override(&mut self, key: &str, value: &str) -> anyhow::Result<()> {
+        match key {
+            "enabled" => self.enabled = value.parse()?,
+            "threshold" => self.threshold = value.parse()?,
+            "min_species_size" => self.min_species_size = value.parse()?,
+            other => anyhow::bail!("unknown config key evolution.speciation.{}", other),
+        }
+        Ok(())
+    }
+}
+
+/// How `GeneHandler` builds the next generation. The `[evolution]` table of the game config.
+/// NEAT populations only use `genome` and `neat`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvolutionConfig {
+    pub genome: GenomeKind,
+    /// What breeds fixed genomes. The fields below up to `speciation` only apply to the genetic algorithm.
+    pub optimizer: OptimizerKind,
+    pub selection: Selection,
+    /// Share of the population copied unchanged into the next generation, best first.
+    pub elite_ratio: f32,
+    pub crossover: Crossover,
+    /// Chance that two different parents are crossed, otherwise the child is a mutated copy of the first.
+    pub crossover_rate: f32,
+    pub mutation: Mutation,
+    pub mutation_rate: MutationRate,
+    pub speciation: SpeciationConfig,
+    pub neat: NeatConfig,
+    /// Best genomes ever scored that are kept across generations and in checkpoints.
+    pub hall_of_fame: usize,
+}
+
+impl Default for EvolutionConfig {
+    fn default() -> Self {
+        EvolutionConfig {
+            genome: GenomeKind::default(),
+            optimizer: OptimizerKind::default(),
+            selection: Selection::default(),
+            elite_ratio: ELITE_RATIO,
+            crossover: Crossover::default(),
+            crossover_rate: 1.0,
+            mutation: Mutation::default(),
+            mutation_rate: MutationRate::default(),
+            speciation: SpeciationConfig::default(),
+            neat: NeatConfig::default(),
+            hall_of_fame: HALL_OF_FAME_SIZE,
+        }
+    }
+}
+
+impl EvolutionConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !(0.0..1.0).contains(&self.elite_ratio) {
+            anyhow::bail!("elite_ratio must be in [0, 1)");
+        }
+        if !(0.0..=1.0).contains(&self.crossover_rate) {
+            anyhow::bail!("crossover_rate must be in [0, 1]");
+        }
+        if let MutationRate::Fixed { rate } = self.mutation_rate {
+            if !(0.0..=1.0).contains(&rate) {
+                anyhow::bail!("mutation_rate must be in [0, 1]");
+            }
+        }
+        if self.speciation.threshold <= 0.0 {
+            anyhow::bail!("speciation.threshold must be positive");
+        }
+        if self.genome == GenomeKind::Neat && self.optimizer != OptimizerKind::Ga {
+            anyhow::bail!("NEAT genomes can only be bred by the genetic algorithm");
+        }
+        self.optimizer.validate()?;
+        self.selection.validate()?;
+        self.crossover.validate()?;
+        self.mutation.validate()?;
+        self.neat.validate()
+    }
+
+    /// Applies one `evolution.KEY=VALUE` override, with the prefix already stripped.
+    pub fn apply_override(&mut self, key: &str, value: &str) -> anyhow::Result<()> {
+        if let Some(key) = key.strip_prefix("neat.") {
+            self.neat.apply_override(key, value)?;
+            return self.validate();
+        }
+        if let Some(key) = key.strip_prefix("speciation.") {
+            self.speciation.apply_override(key, value)?;
+            return self.validate();
+        }
+        match key {
+            "genome" => self.genome = value.parse()?,
+            "optimizer" => self.optimizer = value.parse()?,
+            "hall_of_fame" => self.hall_of_fame = value.parse()?,
+            "selection" => self.selection = value.parse()?,
+            "elite_ratio" => self.elite_ratio = value.parse()?,
+            "crossover" => self.crossover = value.parse()?,
+            "crossover_rate" => self.crossover_rate = value.parse()?,
+            "mutation" => self.mutation = value.parse()?,
+            "mutation_rate" => self.mutation_rate = value.parse()?,
+            other => anyhow::bail!("unknown config key evolution.{}", other),
+        }
+        self.validate()
+    }
+}
@@ -0,0 +1,128 @@
+use std::ops::{AddAssign, DivAssign};
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::BirdScore;
+use crate::game_configs::{GAP_WEIGHT, PIPE_WEIGHT, SURVIVAL_WEIGHT, WASTED_JUMP_PENALTY};
+use crate::resources::sensor_set::PipeObservation;
+
+/// How a bird's `FitnessTerms` add up to its fitness, the `[fitness]` table of the game config.
+///
+/// The defaults score survival time only, like before the other terms existed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FitnessConfig {
+    /// Per second survived.
+    pub survival_weight: f32,
+    /// Per pipe passed.
+    pub pipe_weight: f32,
+    /// Bonus of `gap_weight / (1 + distance)` for the distance to the center of the next gap at death.
+    pub gap_weight: f32,
+    /// Subtracted for every jump made while already rising.
+    pub wasted_jump_penalty: f32,
+}
+
+impl Default for FitnessConfig {
+    fn default() -> Self {
+        FitnessConfig {
+            survival_weight: SURVIVAL_WEIGHT,
+            pipe_weight: PIPE_WEIGHT,
+            gap_weight: GAP_WEIGHT,
+            wasted_jump_penalty: WASTED_JUMP_PENALTY,
+        }
+    }
+}
+
+impl FitnessConfig {
+    /// Applies one `fitness.KEY=VALUE` override, with the prefix already stripped.
+    pub fn apply_override(&mut self, key: &str, value: &str) -> anyhow::Result<()> {
+        let value: f32 = value.parse()?;
+        match key {
+            "survival_weight" => self.survival_weight = value,
+            "pipe_weight" => self.pipe_weight = value,
+            "gap_weight" => self.gap_weight = value,
+            "wasted_jump_penalty" => self.wasted_jump_penalty = value,
+            _ => anyhow::bail!("unknown fitness key {}", key),
+        }
+        self.validate()
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let weights = [self.survival_weight, self.pipe_weight, self.gap_weight, self.wasted_jump_penalty];
+        if weights.iter().any(|weight| !weight.is_finite() || *weight < 0.0) {
+            anyhow::bail!("fitness weights must not be negative");
+        }
+        Ok(())
+    }
+
+    /// Fitness of a bird, never below 0 so selection can treat it as a probability weight.
+    pub fn score(&self, terms: &FitnessTerms) -> f32 {
+        let fitness = self.survival_weight * terms.survival
+            + self.pipe_weight * terms.pipes
+            + self.gap_weight / (1.0 + terms.gap_distance)
+            - self.wasted_jump_penalty * terms.wasted_jumps;
+        fitness.max(0.0)
+    }
+}
+
+/// What one bird achieved in a round, kept per genome for analysis.
+/// Averaged over courses when a generation is played on several.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct FitnessTerms {
+    /// Seconds survived.
+    pub survival: f32,
+    pub pipes: f32,
+    /// Vertical distance to the center of the next gap at death.
+    pub gap_distance: f32,
+    pub jumps: f32,
+    /// Jumps made while already rising.
+    pub wasted_jumps: f32,
+}
+
+impl FitnessTerms {
+    /// Terms of a bird at height `y` with `score` so far.
+    pub fn of_bird(score: &BirdScore, y: f32, next_pipe: &PipeObservation) -> Self {
+        FitnessTerms {
+            survival: score.time_alive,
+            pipes: score.pipes as f32,
+            gap_distance: (y - next_pipe.gap_center()).abs(),
+            jumps: score.jumps as f32,
+            wasted_jumps: score.wasted_jumps as f32,
+        }
+    }
+}
+
+impl AddAssign<&FitnessTerms> for FitnessTerms {
+    fn add_assign(&mut self, other: &FitnessTerms) {
+        self.survival += other.survival;
+        self.pipes += other.pipes;
+        self.gap_distance += other.gap_distance;
+        self.jumps += other.jumps;
+        self.wasted_jumps += other.wasted_jumps;
+    }
+}
+
+impl DivAssign<f32> for FitnessTerms {
+    fn div_assign(&mut self, divisor: f32) {
+        self.survival /= divisor;
+        self.pipes /= divisor;
+        self.gap_distance /= divisor;
+        self.jumps /= divisor;
+        self.wasted_jumps /= divisor;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_scores_survival_time() {
+        let terms = FitnessTerms { survival: 3.25, pipes: 2.0, gap_distance: 0.5, jumps: 9.0, wasted_jumps: 4.0 };
+        assert_eq!(FitnessConfig::default().score(&terms), 3.25);
+
+        let config = FitnessConfig { survival_weight: 0.0, pipe_weight: 1.0, gap_weight: 3.0, wasted_jump_penalty: 0.25 };
+        assert_eq!(config.score(&terms), 2.0 + 2.0 - 1.0);
+        assert_eq!(config.score(&FitnessTerms { wasted_jumps: 100.0, ..terms }), 0.0);
+    }
+}
@@ -0,0 +1,75 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game_configs::{GAME_SPEED, GRAVITY, HOLE_SIZE, JUMP_FORCE, SCREEN_SIZE};
+use crate::resources::{EvolutionConfig, FitnessConfig};
+
+/// Physics and course settings, read by the systems every tick, how birds are scored,
+/// and the `evolution` settings of the `GeneHandler`.
+///
+/// Loaded from a `.toml` or `.json` file, where missing keys keep their default value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameConfig {
+    pub hole_size: f32,
+    pub gravity: f32,
+    pub jump_force: f32,
+    pub game_speed: f32,
+    pub screen_size: [u32; 2],
+    pub fitness: FitnessConfig,
+    pub evolution: EvolutionConfig,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            hole_size: HOLE_SIZE,
+            gravity: GRAVITY,
+            jump_force: JUMP_FORCE,
+            game_speed: GAME_SPEED,
+            screen_size: SCREEN_SIZE,
+            fitness: FitnessConfig::default(),
+            evolution: EvolutionConfig::default(),
+        }
+    }
+}
+
+impl GameConfig {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let config: GameConfig = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")) {
+            serde_json::from_str(&text)?
+        } else {
+            toml::from_str(&text)?
+        };
+        config.fitness.validate()?;
+        config.evolution.validate()?;
+        Ok(config)
+    }
+
+    /// Applies a single `key=value` override, e.g. `gravity=40` or `evolution.selection=tournament:3`.
+    pub fn apply_override(&mut self, assignment: &str) -> anyhow::Result<()> {
+        let (key, value) = assignment.split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("expected key=value, got {}", assignment))?;
+        let value = value.trim();
+        match key.trim() {
+            "hole_size" => self.hole_size = value.parse()?,
+            "gravity" => self.gravity = value.parse()?,
+            "jump_force" => self.jump_force = value.parse()?,
+            "game_speed" => self.game_speed = value.parse()?,
+            "screen_size" => {
+                let (width, height) = value.split_once('x')
+                    .ok_or_else(|| anyhow::anyhow!("expected screen_size=WIDTHxHEIGHT, got {}", value))?;
+                self.screen_size = [width.parse()?, height.parse()?];
+            }
+            other => match (other.strip_prefix("evolution."), other.strip_prefix("fitness.")) {
+                (Some(key), _) => self.evolution.apply_override(key, value)?,
+                (_, Some(key)) => self.fitness.apply_override(key, value)?,
+                _ => anyhow::bail!("unknown config key {}", other),
+            },
+        }
+        Ok(())
+    }
+}
@@ -1,48 +1,250 @@
-use rand::{Rng, thread_rng};
-use rand::rngs::ThreadRng;
-use crate::components::DNA;
-use crate::game_configs::GENE_SIZE;
+use instant::Instant;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use serde::Serialize;
+use crate::components::{Activation, DNA};
+use crate::game_configs::GENE_RANGE;
+use crate::resources::{EvolutionConfig, FitnessTerms, HallOfFame, NeatPopulation, NetworkShape, Optimizer, OptimizerKind, Origin};
+use crate::resources::genetic_algorithm::weight_distance;
+use crate::resources::network_file::{NetworkFile, NETWORK_FILE_VERSION};
+use crate::resources::population_file::{GenomeRecord, PopulationFile, POPULATION_FILE_VERSION};
+use crate::resources::sim_rng::{derive_seed, random_seed, GENE_EVOLVE_STREAM, GENE_INIT_STREAM};
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct GenerationSummary {
+    pub generation: usize,
+    pub best_fitness: f32,
+    pub average_fitness: f32,
+    pub best_index: usize,
+    /// 1 unless the population is speciated.
+    pub species: usize,
+    pub worst_fitness: f32,
+    pub median_fitness: f32,
+    pub fitness_std_dev: f32,
+    pub best_pipes: f32,
+    pub average_pipes: f32,
+    pub average_gap_distance: f32,
+    pub average_wasted_jumps: f32,
+    /// Mean distance between two genomes: RMS weight difference, or NEAT's compatibility distance.
+    pub diversity: f32,
+    /// See `Optimizer::mutation_rate`.
+    pub mutation_rate: f32,
+    /// Seconds from the start of the generation until it was summarized.
+    pub wall_time: f32,
+}
 
+/// Identity of a genome, kept while it is copied unchanged from one generation to the next.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lineage {
+    pub id: usize,
+    /// Genome it was bred from, the fitter one of a crossover.
+    pub parent: Option<usize>,
+    /// Generation it first played in.
+    pub born: usize,
+}
 
+#[derive(Clone)]
+pub struct Champion {
+    pub generation: usize,
+    pub fitness: f32,
+    pub genes: Vec<f32>,
+}
 
-const EVOLVE_OFFSET : f32 = 0.1;
-const SURVIVE_RATIO : f32 = 0.1;
 pub struct GeneHandler {
-    gene_container: Vec<[f32;GENE_SIZE]>,
+    shape: NetworkShape,
+    gene_container: Vec<Vec<f32>>,
     fitness: Vec<f32>,
+    /// What every genome's bird achieved, averaged over courses.
+    terms: Vec<FitnessTerms>,
     pub generation: usize,
-    rng : ThreadRng,
-    gene_count : usize
+    pub last_summary: Option<GenerationSummary>,
+    pub champion: Option<Champion>,
+    /// Best fitness of the last processed generation.
+    pub previous_best: Option<f32>,
+    lineage: Vec<Lineage>,
+    next_lineage_id: usize,
+    seed : u64,
+    rng : StdRng,
+    gene_count : usize,
+    evolution: EvolutionConfig,
+    optimizer: Box<dyn Optimizer>,
+    neat: Option<NeatPopulation>,
+    hall_of_fame: HallOfFame,
+    /// Hall of fame genomes that replace the last genomes of the next generation.
+    reintroduced: Vec<Vec<f32>>,
+    generation_start: Instant,
 }
 
 impl Default for GeneHandler {
     fn default() -> Self {
+        GeneHandler::new(random_seed(), NetworkShape::default())
+    }
+}
+
+impl GeneHandler {
+
+    pub fn new(seed: u64, shape: NetworkShape) -> Self {
         let mut gene_handler = GeneHandler{
+            shape,
             gene_container : vec![],
             fitness: vec![],
+            terms: vec![],
             generation : 0,
-            rng : thread_rng(),
-            gene_count : 100
+            last_summary : None,
+            champion : None,
+            previous_best: None,
+            lineage: vec![],
+            next_lineage_id: 0,
+            seed,
+            rng : StdRng::seed_from_u64(derive_seed(seed, GENE_INIT_STREAM, 0)),
+            gene_count : 100,
+            evolution: EvolutionConfig::default(),
+            optimizer: EvolutionConfig::default().optimizer.build(&EvolutionConfig::default()),
+            neat: None,
+            hall_of_fame: HallOfFame::new(EvolutionConfig::default().hall_of_fame),
+            reintroduced: vec![],
+            generation_start: Instant::now(),
         };
 
         gene_handler.initialize();
-        return gene_handler;
+        gene_handler
     }
-}
 
-impl GeneHandler {
+    /// Population of a single, fixed genome that is never evolved.
+    pub fn from_network_file(file: &NetworkFile, seed: u64) -> anyhow::Result<Self> {
+        let shape = NetworkShape::from_layers(file.layers.clone(), file.activations.clone(), file.sensors.clone())?;
+        Ok(GeneHandler::from_genome(file.genes.clone(), shape, file.generation, seed))
+    }
+
+    /// Population of a single, fixed genome of `shape`, like `from_network_file`.
+    pub fn from_genome(genes: Vec<f32>, shape: NetworkShape, generation: usize, seed: u64) -> Self {
+        let mut gene_handler = GeneHandler::new(seed, shape);
+
+        gene_handler.gene_container = vec![genes];
+        gene_handler.fitness = vec![-1.0];
+        gene_handler.terms = vec![FitnessTerms::default()];
+        gene_handler.gene_count = 1;
+        gene_handler.lineage.truncate(1);
+        gene_handler.generation = generation;
+        gene_handler
+    }
 
-    pub fn get_alive_gene(&self , index : usize)-> [f32;GENE_SIZE]{
-        return self.gene_container[index].clone()
+    pub fn set_evolution(&mut self, evolution: &EvolutionConfig) {
+        self.optimizer = evolution.optimizer.build(evolution);
+        self.hall_of_fame.set_capacity(evolution.hall_of_fame);
+        self.evolution = evolution.clone();
+        self.optimizer.start(&self.gene_container, &mut self.rng);
+    }
+
+    /// Species id of genome `index` while the population is speciated, for tinting birds.
+    pub fn species_of(&self, index: usize) -> Option<usize> {
+        match &self.neat {
+            Some(neat) => neat.species_of(index),
+            None => self.optimizer.species_of(index),
+        }
+    }
+
+    pub fn species_count(&self) -> usize {
+        match &self.neat {
+            Some(neat) => neat.species.species.len(),
+            None => self.optimizer.species_count(),
+        }
+    }
+
+    /// Replaces the population with minimal NEAT genomes reading the same sensors.
+    /// `gene_container` then only holds empty genomes to keep indices valid.
+    pub fn use_neat(&mut self, hidden_activation: Activation) {
+        self.neat = Some(NeatPopulation::new(
+            self.gene_count,
+            self.shape.input_size(),
+            self.shape.output_size(),
+            hidden_activation,
+            *self.shape.activations.last().unwrap(),
+            &self.evolution.neat,
+            &mut self.rng));
+        self.gene_container = vec![vec![]; self.gene_count];
+    }
+
+    pub fn is_neat(&self) -> bool {
+        self.neat.is_some()
+    }
+
+    pub fn lineage(&self, index: usize) -> Lineage {
+        self.lineage[index]
+    }
+
+    fn new_lineage(&mut self, parent: Option<usize>) -> Lineage {
+        self.next_lineage_id += 1;
+        Lineage { id: self.next_lineage_id, parent, born: self.generation }
+    }
+
+    /// Follows every genome of the new generation back to its parent. Call after `generation` moved on.
+    fn advance_lineage(&mut self, origins: Vec<Origin>) {
+        let previous = std::mem::take(&mut self.lineage);
+        for origin in origins {
+            let lineage = match origin {
+                Origin::Elite(index) => previous[index],
+                Origin::Offspring(index) => self.new_lineage(Some(previous[index].id)),
+                Origin::Sampled => self.new_lineage(None),
+            };
+            self.lineage.push(lineage);
+        }
+    }
+
+    pub fn get_alive_gene(&self , index : usize)-> Vec<f32>{
+        self.gene_container[index].clone()
+    }
+
+    pub fn shape(&self) -> &NetworkShape {
+        &self.shape
+    }
+
+    pub fn gene_count(&self) -> usize {
+        self.gene_count
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn fitness(&self) -> &[f32] {
+        &self.fitness
+    }
+
+    /// Unscored copies of the genomes at `indices`, in that order, as a population of their own
+    /// so they can be played in another world. Only the genomes and the network shape are carried over.
+    pub fn batch(&self, indices: &[usize]) -> GeneHandler {
+        GeneHandler {
+            shape: self.shape.clone(),
+            gene_container: indices.iter().map(|i| self.gene_container[*i].clone()).collect(),
+            fitness: vec![-1.0; indices.len()],
+            terms: vec![FitnessTerms::default(); indices.len()],
+            generation: self.generation,
+            last_summary: None,
+            champion: None,
+            previous_best: None,
+            lineage: indices.iter().map(|i| self.lineage[*i]).collect(),
+            next_lineage_id: self.next_lineage_id,
+            seed: self.seed,
+            rng: StdRng::seed_from_u64(self.seed),
+            gene_count: indices.len(),
+            evolution: EvolutionConfig::default(),
+            optimizer: EvolutionConfig::default().optimizer.build(&EvolutionConfig::default()),
+            neat: self.neat.as_ref().map(|neat| neat.subset(indices)),
+            hall_of_fame: HallOfFame::default(),
+            reintroduced: vec![],
+            generation_start: Instant::now(),
+        }
     }
     pub fn initialize(&mut self){
+        let gene_size = self.shape.gene_size();
         for _ in 0..self.gene_count {
-            let mut genes = [0f32;GENE_SIZE];
-            for i in 0..GENE_SIZE {
-                genes[i] = self.rng.gen_range(-16.0..16.0);
-            }
+            let genes = (0..gene_size).map(|_| self.rng.gen_range(-GENE_RANGE..GENE_RANGE)).collect();
             self.gene_container.push(genes);
             self.fitness.push(-1.0f32);
+            self.terms.push(FitnessTerms::default());
+            let lineage = self.new_lineage(None);
+            self.lineage.push(lineage);
         }
     }
     pub fn get_dna(&mut self) -> DNA{
@@ -54,11 +256,11 @@ impl GeneHandler {
             }
 
 
-        let genes = self.gene_container[index];
+        let genes = self.gene_container[index].clone();
         DNA{
-            hidden_layers: [6,4],
             genes,
             index,
+            graph: self.neat.as_ref().map(|neat| neat.genomes[index].compile()),
         }
     }
 
@@ -68,137 +270,390 @@ impl GeneHandler {
 
     }
 
+    pub fn set_terms(&mut self, index: usize, terms: FitnessTerms) {
+        self.terms[index] = terms;
+    }
 
-    fn pick_gene_by_fitness(&mut self, accumulated_array : &Vec<f32>)-> usize{
-        let max = accumulated_array.last().unwrap();
-        let rnd = self.rng.gen_range(0.0..*max);
-        let mut index = 0;
-        for i in 0..accumulated_array.len() {
-            if accumulated_array[i] > rnd {
-                index = i;
-                break;
-            }
+    pub fn fitness_terms(&self) -> &[FitnessTerms] {
+        &self.terms
+    }
+
+    /// Marks every genome unscored for the next round.
+    pub fn clear_scores(&mut self) {
+        self.fitness.iter_mut().for_each(|score| *score = -1.0f32);
+        self.terms.iter_mut().for_each(|terms| *terms = FitnessTerms::default());
+        self.generation_start = Instant::now();
+    }
+
+    /// Every genome has been handed out and scored, so the generation can be processed.
+    pub fn is_evaluated(&self) -> bool {
+        self.fitness.iter().all(|&f| f >= 0.0)
+    }
+
+    /// Best genome seen so far, including birds of the running generation that already died.
+    pub fn best_genome(&self) -> Option<Champion> {
+        let current = self.summarize();
+        match &self.champion {
+            Some(champion) if champion.fitness >= current.best_fitness => Some(champion.clone()),
+            _ if current.best_fitness > 0.0 => Some(Champion {
+                generation: self.generation,
+                fitness: current.best_fitness,
+                genes: self.gene_container[current.best_index].clone(),
+            }),
+            _ => None,
         }
+    }
 
-        return index;
+    pub fn to_network_file(&self) -> Option<NetworkFile> {
+        if self.is_neat() {
+            return None;
+        }
+        let champion = self.best_genome()?;
+        Some(NetworkFile {
+            version: NETWORK_FILE_VERSION,
+            layers: self.shape.layers.clone(),
+            activations: self.shape.activations.clone(),
+            sensors: Some(self.shape.sensors.clone()),
+            generation: champion.generation,
+            fitness: champion.fitness,
+            genes: champion.genes,
+        })
     }
 
-    pub fn process_generation(&mut self){
+    /// Scores the same genomes again instead of evolving them, used when replaying a fixed network.
+    pub fn replay_generation(&mut self) {
+        let summary = self.summarize();
+        self.last_summary = Some(summary);
+        self.previous_best = Some(summary.best_fitness);
+        self.clear_scores();
+        self.generation += 1;
+    }
 
-        let mut next_generation_genes = vec![];
+    pub fn to_population_file(&self) -> PopulationFile {
+        PopulationFile {
+            version: POPULATION_FILE_VERSION,
+            generation: self.generation,
+            seed: self.seed,
+            layers: self.shape.layers.clone(),
+            activations: self.shape.activations.clone(),
+            sensors: Some(self.shape.sensors.clone()),
+            genomes: self.gene_container.iter().zip(&self.fitness).zip(&self.terms)
+                .map(|((genes, &fitness), &terms)| GenomeRecord { fitness, genes: genes.clone(), terms })
+                .collect(),
+            hall_of_fame: self.hall_of_fame.entries.clone(),
+            optimizer: self.optimizer.state(),
+        }
+    }
 
+    pub fn hall_of_fame(&self) -> &HallOfFame {
+        &self.hall_of_fame
+    }
 
-        //1. 상위 10%는 그대로 이어감
-        let mut indexed_fitness: Vec<(usize, f32)> = self.fitness.iter().cloned().enumerate().collect();
-        indexed_fitness.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-        let sorted_indices: Vec<usize> = indexed_fitness.iter().map(|(idx, _)| *idx).collect();
-        let survive_count = (self.gene_count as f32 * SURVIVE_RATIO) as usize;
-        for i in 0..survive_count {
-            next_generation_genes.push( self.gene_container[sorted_indices[i]].clone());
+    /// Queues hall of fame entry `index` to replace the last genome of the next generation.
+    pub fn reintroduce(&mut self, index: usize) -> anyhow::Result<()> {
+        if self.is_neat() {
+            anyhow::bail!("NEAT populations have no hall of fame");
+        }
+        if self.evolution.optimizer != OptimizerKind::Ga {
+            // their distribution would be updated with genomes it never sampled
+            anyhow::bail!("CMA-ES and NES only score their own samples, genomes can't be reintroduced");
         }
+        let entry = self.hall_of_fame.entries.get(index)
+            .ok_or_else(|| anyhow::anyhow!("no hall of fame entry {}", index))?;
+        if self.reintroduced.len() >= self.gene_count {
+            anyhow::bail!("the whole next generation is already reintroduced");
+        }
+        self.reintroduced.push(entry.genes.clone());
+        Ok(())
+    }
+
+    /// Genomes queued by `reintroduce` for the next generation.
+    pub fn reintroduced_count(&self) -> usize {
+        self.reintroduced.len()
+    }
 
-        let mut accumulated_fitness = vec![0.0f32; self.fitness.len()];
-        let mut sum = 0.0f32;
-        for (i, &element) in self.fitness.iter().enumerate() {
-            sum += element;
-            accumulated_fitness[i] = sum;
+    /// Replaces the population, and its network shape, with a saved one.
+    /// A fully evaluated population moves straight on to the next generation,
+    /// a partially evaluated one is played again from the start.
+    pub fn restore(&mut self, file: PopulationFile) -> anyhow::Result<()> {
+        let shape = NetworkShape::from_layers(file.layers, file.activations, file.sensors)?;
+        if file.genomes.is_empty() {
+            anyhow::bail!("population file has no genomes");
         }
 
+        let mut gene_container = Vec::with_capacity(file.genomes.len());
+        let mut fitness = Vec::with_capacity(file.genomes.len());
+        let mut terms = Vec::with_capacity(file.genomes.len());
+        for genome in file.genomes {
+            if genome.genes.len() != shape.gene_size() {
+                anyhow::bail!("genome has {} genes, expected {}", genome.genes.len(), shape.gene_size());
+            }
+            gene_container.push(genome.genes);
+            fitness.push(genome.fitness);
+            terms.push(genome.terms);
+        }
+        if file.hall_of_fame.iter().any(|entry| entry.genes.len() != shape.gene_size()) {
+            anyhow::bail!("hall of fame genome doesn't match the network shape");
+        }
+        if file.optimizer.as_ref().is_some_and(|state| state.gene_size() != shape.gene_size()) {
+            anyhow::bail!("optimizer state doesn't match the network shape");
+        }
 
-        let average = self.fitness.iter().sum::<f32>() / self.fitness.len() as f32;
-        let probability = 1.0f32 / f32::max(1.0, average);
+        self.shape = shape;
+        self.gene_count = gene_container.len();
+        self.gene_container = gene_container;
+        self.terms = terms;
+        self.fitness = fitness;
+        self.generation = file.generation;
+        self.seed = file.seed;
+        self.last_summary = None;
+        self.champion = None;
+        self.previous_best = None;
+        self.lineage = (0..self.gene_count).map(|_| self.new_lineage(None)).collect();
+        self.hall_of_fame.restore(file.hall_of_fame);
+        self.reintroduced.clear();
+        self.optimizer.start(&self.gene_container, &mut self.rng);
+        if let Some(state) = file.optimizer {
+            self.optimizer.resume(state);
+        }
 
+        if self.is_evaluated() {
+            self.process_generation();
+            self.last_summary = None;
+        } else {
+            self.clear_scores();
+        }
+        Ok(())
+    }
 
-        let remain_gene_count = self.gene_count - survive_count;
-        for _ in 0..remain_gene_count {
-            let gene_index_0 = self.pick_gene_by_fitness(&accumulated_fitness);
-            let gene_index_1 = self.pick_gene_by_fitness(&accumulated_fitness);
+    pub fn summarize(&self) -> GenerationSummary {
+        // `restore` refuses empty populations, every other one is built with at least one genome
+        debug_assert!(!self.fitness.is_empty(), "summarizing an empty population");
+        let (best_index, best_fitness) = self.fitness.iter().cloned().enumerate()
+            .fold((0, f32::MIN), |best, (i, f)| if f > best.1 { (i, f) } else { best });
+        let count = self.fitness.len() as f32;
+        let average_fitness = self.fitness.iter().sum::<f32>() / count;
+        let mut sorted = self.fitness.clone();
+        sorted.sort_by(f32::total_cmp);
+        let middle = sorted.len() / 2;
+        let median_fitness = if sorted.len().is_multiple_of(2) { (sorted[middle - 1] + sorted[middle]) * 0.5 } else { sorted[middle] };
+        let variance = self.fitness.iter().map(|f| (f - average_fitness) * (f - average_fitness)).sum::<f32>() / count;
+        let mutation_rate = match &self.neat {
+            Some(_) => self.evolution.neat.weight_mutation_rate,
+            None => self.optimizer.mutation_rate(&self.fitness),
+        };
 
+        GenerationSummary {
+            generation: self.generation,
+            best_fitness,
+            average_fitness,
+            best_index,
+            species: self.species_count(),
+            worst_fitness: sorted[0],
+            median_fitness,
+            fitness_std_dev: variance.sqrt(),
+            best_pipes: self.terms.iter().map(|terms| terms.pipes).fold(0.0, f32::max),
+            average_pipes: self.terms.iter().map(|terms| terms.pipes).sum::<f32>() / count,
+            average_gap_distance: self.terms.iter().map(|terms| terms.gap_distance).sum::<f32>() / count,
+            average_wasted_jumps: self.terms.iter().map(|terms| terms.wasted_jumps).sum::<f32>() / count,
+            diversity: self.diversity(),
+            mutation_rate,
+            wall_time: self.generation_start.elapsed().as_secs_f32(),
+        }
+    }
 
-            let new_gene = if gene_index_0 == gene_index_1 {
-                self.evolve( gene_index_0 , probability)
+    /// Mean distance over all pairs of genomes.
+    fn diversity(&self) -> f32 {
+        let count = self.gene_count;
+        if count < 2 {
+            return 0.0;
+        }
+        let mut total = 0.0;
+        for a in 0..count {
+            for b in a + 1..count {
+                total += match &self.neat {
+                    Some(neat) => neat.genomes[a].distance(&neat.genomes[b], &self.evolution.neat),
+                    None => weight_distance(&self.gene_container[a], &self.gene_container[b]),
+                };
             }
-            else {
-                self.make_child(gene_index_0,gene_index_1, probability)
-            };
+        }
+        total / (count * (count - 1) / 2) as f32
+    }
 
-            next_generation_genes.push(new_gene);
+    pub fn process_generation(&mut self){
+        // every generation draws from its own stream, so resuming a checkpoint evolves exactly like the original run
+        self.rng = StdRng::seed_from_u64(derive_seed(self.seed, GENE_EVOLVE_STREAM, self.generation as u64));
+
+        let summary = self.summarize();
+        if self.champion.as_ref().is_none_or(|c| summary.best_fitness > c.fitness) {
+            self.champion = Some(Champion {
+                generation: summary.generation,
+                fitness: summary.best_fitness,
+                genes: self.gene_container[summary.best_index].clone(),
+            });
+        }
+        self.last_summary = Some(summary);
+        self.previous_best = Some(summary.best_fitness);
+        self.hall_of_fame.record(self.generation, self.seed, &self.gene_container, &self.fitness);
+
+        if let Some(neat) = self.neat.as_mut() {
+            neat.process_generation(&self.fitness, &self.evolution.neat, &mut self.rng);
+            let origins = neat.origins.clone();
+            self.clear_scores();
+            self.generation += 1;
+            self.advance_lineage(origins);
+            return;
         }
 
+        let next_generation_genes = self.optimizer.next_generation(&self.gene_container, &self.fitness, &self.shape, &mut self.rng);
 
-        for score in self.fitness.iter_mut() {
-            *score = -1.0f32;
-        }
+
+        self.clear_scores();
         self.generation += 1;
 
         assert_eq!(self.gene_container.len() , next_generation_genes.len());
         self.gene_container = next_generation_genes;
+        let origins = (0..self.gene_count).map(|index| self.optimizer.origin(index)).collect();
+        self.advance_lineage(origins);
+
+        // the genetic algorithm puts its elites first, so reintroduced genomes replace the tail
+        let start = self.gene_count - self.reintroduced.len();
+        for (slot, genes) in self.gene_container[start..].iter_mut().zip(self.reintroduced.drain(..)) {
+            *slot = genes;
+        }
+        for index in start..self.gene_count {
+            self.lineage[index] = self.new_lineage(None);
+        }
+        self.optimizer.adopt(&self.gene_container, &mut self.rng);
     }
+}
 
-    // fn make_genes(&mut self) -> [f32;GENE_SIZE]{
-    //     let mut genes = [0f32;GENE_SIZE];
-    //     for i in 0..GENE_SIZE {
-    //         genes[i] = self.rng.gen_range(-16.0..16.0);
-    //     }
-    //     return genes;
-    // }
-
-    fn evolve(&mut self, gene_index: usize, probability : f32 )-> [f32;GENE_SIZE]{
-        let mut gene = self.gene_container[gene_index].clone();
-        // let mut evolve_count = 0;
-        for g in gene.iter_mut() {
-            let change : f32 = self.rng.gen_range(0.0..1.0);
-            if change < probability {
-                let offset = self.rng.gen_range(-EVOLVE_OFFSET..EVOLVE_OFFSET);
-                *g = *g + offset;
-                // evolve_count+=1;
-            }
+#[cfg(test)]
+mod test{
+    use super::*;
+    use crate::resources::HallOfFameEntry;
+
+
+    #[test]
+    fn test_same_seed_same_evolution(){
+        let mut a = GeneHandler::new(42, NetworkShape::default());
+        let mut b = GeneHandler::new(42, NetworkShape::default());
+        assert_eq!(a.gene_container, b.gene_container);
+
+        for i in 0..a.gene_count {
+            a.set_score( i , (i % 7) as f32 );
+            b.set_score( i , (i % 7) as f32 );
         }
+        a.process_generation();
+        b.process_generation();
+        assert_eq!(a.gene_container, b.gene_container);
+        assert_ne!(a.gene_container, GeneHandler::new(43, NetworkShape::default()).gene_container);
+    }
 
-        return gene;
-        // log::info!("{:.5} : evolve {}" , probability, evolve_count);
+    #[test]
+    fn test_population_file_round_trip(){
+        let mut gene_handler = GeneHandler::default();
+        for i in 0..gene_handler.gene_count {
+            gene_handler.set_score( i , i as f32 );
+        }
+
+        for name in ["population_test.json", "population_test.bin"] {
+            let path = std::env::temp_dir().join(format!("{}_{}", std::process::id(), name));
+            gene_handler.to_population_file().save(&path).unwrap();
+
+            let mut restored = GeneHandler::default();
+            restored.restore(PopulationFile::load(&path).unwrap()).unwrap();
+            std::fs::remove_file(&path).unwrap();
+
+            let mut expected = GeneHandler::default();
+            expected.restore(gene_handler.to_population_file()).unwrap();
+            assert_eq!(restored.generation, 1);
+            assert_eq!(restored.seed, gene_handler.seed);
+            assert_eq!(restored.gene_container, expected.gene_container);
+        }
     }
 
-    fn make_child(&mut self, gene_index_0: usize,gene_index_1: usize, probability : f32 )-> [f32;GENE_SIZE]{
-        let mut gene = [0.0f32;GENE_SIZE];
-        let cross_point = self.rng.gen_range(0..GENE_SIZE);
-        for i in 0..GENE_SIZE {
-            let change : f32 = self.rng.gen_range(0.0..1.0);
-            if change < probability {
-                gene[i] = self.rng.gen_range(-16.0..16.0);
+    #[test]
+    fn test_resumed_cma_es_keeps_its_distribution(){
+        let evolution = EvolutionConfig { optimizer: OptimizerKind::CmaEs { sigma: 0.5 }, ..EvolutionConfig::default() };
+        let mut gene_handler = GeneHandler::new(42, NetworkShape::default());
+        gene_handler.set_evolution(&evolution);
+        for _ in 0..3 {
+            for i in 0..gene_handler.gene_count {
+                gene_handler.set_score( i , 1.0 / (1.0 + gene_handler.gene_container[i].iter().map(|g| g * g).sum::<f32>()) );
             }
-            else {
-                let distrib =1.0f32 +  self.rng.gen_range(0.0f32..probability);
-                gene[i] = if i < cross_point {
-                    self.gene_container[gene_index_0][i]
-                } else {
-                    self.gene_container[gene_index_1][i]
-                } * distrib;
+            if gene_handler.generation < 2 {
+                gene_handler.process_generation();
             }
         }
 
+        let path = std::env::temp_dir().join(format!("population_cma_es_test_{}.bin", std::process::id()));
+        gene_handler.to_population_file().save(&path).unwrap();
+        let mut restored = GeneHandler::default();
+        restored.set_evolution(&evolution);
+        restored.restore(PopulationFile::load(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
 
-        return gene;
+        gene_handler.process_generation();
+        assert_eq!(restored.generation, 3);
+        assert_eq!(restored.gene_container, gene_handler.gene_container);
     }
-}
 
-#[cfg(test)]
-mod test{
-    use super::*;
+    #[test]
+    fn test_reintroduced_genome_is_speciated(){
+        let mut gene_handler = GeneHandler::new(42, NetworkShape::default());
+        let mut evolution = EvolutionConfig::default();
+        evolution.speciation.enabled = true;
+        gene_handler.set_evolution(&evolution);
+        for i in 0..gene_handler.gene_count {
+            gene_handler.set_score( i , 1.0 + (i % 7) as f32 );
+        }
+        // far from every genome of the population, it can only found a species of its own
+        let genes = vec![50.0; gene_handler.shape.gene_size()];
+        gene_handler.hall_of_fame.restore(vec![HallOfFameEntry { generation: 0, fitness: 1.0, seed: 42, genes }]);
+        gene_handler.reintroduce(0).unwrap();
+        gene_handler.process_generation();
+
+        let last = gene_handler.gene_count - 1;
+        let species = gene_handler.species_of(last);
+        assert!(species.is_some());
+        assert!((0..last).all(|index| gene_handler.species_of(index) != species));
+
+        gene_handler.set_evolution(&EvolutionConfig { optimizer: OptimizerKind::CmaEs { sigma: 0.5 }, ..EvolutionConfig::default() });
+        assert!(gene_handler.reintroduce(0).is_err());
+    }
 
+    #[test]
+    fn test_lineage_follows_elites(){
+        let mut gene_handler = GeneHandler::new(42, NetworkShape::default());
+        for i in 0..gene_handler.gene_count {
+            gene_handler.set_score( i , i as f32 );
+        }
+        let best = gene_handler.lineage(gene_handler.gene_count - 1);
+        gene_handler.process_generation();
+
+        // the elites come first, the best genome keeps its id
+        assert_eq!(gene_handler.lineage(0), best);
+        let child = gene_handler.lineage(gene_handler.gene_count - 1);
+        assert!(child.id > best.id);
+        assert_eq!(child.born, 1);
+        assert!(child.parent.is_some());
+    }
 
     #[test]
-    fn test_evolve(){
-        let mut gene_handler = GeneHandler::default();
+    fn test_speciated_generation(){
+        let mut gene_handler = GeneHandler::new(42, NetworkShape::default());
+        let mut evolution = EvolutionConfig::default();
+        evolution.speciation.enabled = true;
+        gene_handler.set_evolution(&evolution);
+        assert!(gene_handler.species_count() > 1);
 
         for i in 0..gene_handler.gene_count {
-            gene_handler.set_score( i , i as f32 * 0.2 );
+            gene_handler.set_score( i , (i % 7) as f32 );
         }
-
-        let average = gene_handler.fitness.iter().sum::<f32>() / gene_handler.fitness.len() as f32;
-        let probability = 1.0f32 / f32::max(1.0, average);
-        // log::info!("before : \t{:?}" ,gene_handler.gene_container[0] );
-        gene_handler.evolve(0 , probability);
-        // log::info!("after : \t{:?}" ,gene_handler.gene_container[0] );
+        gene_handler.process_generation();
+        assert_eq!(gene_handler.gene_container.len(), gene_handler.gene_count);
+        assert!(gene_handler.species_of(gene_handler.gene_count - 1).is_some());
+        assert_eq!(gene_handler.summarize().species, gene_handler.species_count());
     }
 }
\ No newline at end of file
@@ -0,0 +1,231 @@
+use rand::Rng;
+use rand::rngs::StdRng;
+
+use crate::resources::optimizer::{Optimizer, Origin};
+use crate::resources::species::SpeciesSet;
+use crate::resources::{CrossoverOperator, EvolutionConfig, MutationOperator, NetworkShape, SelectionStrategy};
+
+/// Elites, parent selection, crossover and mutation, optionally within weight-space species.
+pub struct GeneticAlgorithm {
+    evolution: EvolutionConfig,
+    selection: Box<dyn SelectionStrategy>,
+    crossover: Box<dyn CrossoverOperator>,
+    mutation: Box<dyn MutationOperator>,
+    species: SpeciesSet<Vec<f32>>,
+    /// Species id of every genome, empty unless the population is speciated.
+    species_ids: Vec<usize>,
+    origins: Vec<Origin>,
+}
+
+/// The population being bred from, passed around while building the next generation.
+struct Parents<'a> {
+    population: &'a [Vec<f32>],
+    fitness: &'a [f32],
+    shape: &'a NetworkShape,
+    mutation_rate: f32,
+}
+
+impl GeneticAlgorithm {
+    pub fn new(evolution: &EvolutionConfig) -> Self {
+        GeneticAlgorithm {
+            evolution: evolution.clone(),
+            selection: evolution.selection.build(),
+            crossover: evolution.crossover.build(),
+            mutation: evolution.mutation.build(),
+            species: SpeciesSet::default(),
+            species_ids: vec![],
+            origins: vec![],
+        }
+    }
+
+    /// Groups the population into species by weight distance, if enabled.
+    fn speciate(&mut self, population: &[Vec<f32>], rng: &mut StdRng) {
+        if !self.evolution.speciation.enabled {
+            return;
+        }
+        let threshold = self.evolution.speciation.threshold;
+        self.species.speciate(population, threshold, |a, b| weight_distance(a, b), rng);
+        self.species_ids = self.species.species_of(population.len());
+    }
+
+    /// Adds `count` children of parents picked among `members`.
+    fn breed(&mut self, parents: &Parents, members: &[usize], count: usize, rng: &mut StdRng, next_generation_genes: &mut Vec<Vec<f32>>) {
+        if count == 0 {
+            return;
+        }
+        let fitness: Vec<f32> = members.iter().map(|m| parents.fitness[*m]).collect();
+        self.selection.prepare(&fitness, count * 2, rng);
+        for _ in 0..count {
+            let gene_index_0 = members[self.selection.pick(rng)];
+            let gene_index_1 = members[self.selection.pick(rng)];
+
+
+            let new_gene = if gene_index_0 == gene_index_1 {
+                self.evolve(&parents.population[gene_index_0], parents.mutation_rate, rng)
+            }
+            else {
+                self.make_child(parents, gene_index_0, gene_index_1, rng)
+            };
+
+            next_generation_genes.push(new_gene);
+            self.origins.push(Origin::Offspring(gene_index_0));
+        }
+    }
+
+    /// Reorders genomes sorted best first so the best of every species comes first, then every
+    /// species' second best, and so on. Elites then spread over the species.
+    fn interleave_species(&self, sorted_indices: &[usize]) -> Vec<usize> {
+        let mut rank_in_species = vec![0; sorted_indices.len()];
+        let mut seen = std::collections::HashMap::new();
+        for index in sorted_indices {
+            let rank = seen.entry(self.species_ids[*index]).or_insert(0);
+            rank_in_species[*index] = *rank;
+            *rank += 1;
+        }
+        let mut interleaved = sorted_indices.to_vec();
+        interleaved.sort_by_key(|index| rank_in_species[*index]);
+        interleaved
+    }
+
+    fn evolve(&self, genes: &[f32], probability : f32, rng: &mut StdRng)-> Vec<f32>{
+        let mut gene = genes.to_vec();
+        self.mutation.mutate(&mut gene, probability, rng);
+        gene
+    }
+
+    fn make_child(&self, parents: &Parents, gene_index_0: usize,gene_index_1: usize, rng: &mut StdRng)-> Vec<f32>{
+        let crossover_rate = self.evolution.crossover_rate;
+        if crossover_rate < 1.0 && !rng.gen_bool(crossover_rate as f64) {
+            return self.evolve(&parents.population[gene_index_0], parents.mutation_rate, rng);
+        }
+
+        let mut gene = self.crossover.cross(
+            &parents.population[gene_index_0],
+            &parents.population[gene_index_1],
+            parents.shape,
+            parents.mutation_rate,
+            rng);
+        if !self.crossover.mutates() {
+            self.mutation.mutate(&mut gene, parents.mutation_rate, rng);
+        }
+        gene
+    }
+}
+
+impl Optimizer for GeneticAlgorithm {
+    fn start(&mut self, population: &[Vec<f32>], rng: &mut StdRng) {
+        self.species = SpeciesSet::default();
+        self.species_ids.clear();
+        self.speciate(population, rng);
+    }
+
+    fn next_generation(&mut self, population: &[Vec<f32>], fitness: &[f32], shape: &NetworkShape, rng: &mut StdRng) -> Vec<Vec<f32>> {
+        let gene_count = population.len();
+        let mut next_generation_genes = vec![];
+        self.origins.clear();
+
+
+        //1. 상위 10%는 그대로 이어감
+        let mut indexed_fitness: Vec<(usize, f32)> = fitness.iter().cloned().enumerate().collect();
+        indexed_fitness.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        let mut sorted_indices: Vec<usize> = indexed_fitness.iter().map(|(idx, _)| *idx).collect();
+        if !self.species_ids.is_empty() {
+            sorted_indices = self.interleave_species(&sorted_indices);
+        }
+        let survive_count = (gene_count as f32 * self.evolution.elite_ratio) as usize;
+        for i in 0..survive_count {
+            next_generation_genes.push( population[sorted_indices[i]].clone());
+            self.origins.push(Origin::Elite(sorted_indices[i]));
+        }
+
+        let average = fitness.iter().sum::<f32>() / fitness.len() as f32;
+        let parents = Parents {
+            population,
+            fitness,
+            shape,
+            mutation_rate: self.evolution.mutation_rate.rate(average),
+        };
+
+
+        let remain_gene_count = gene_count - survive_count;
+        if self.species_ids.is_empty() {
+            let everyone: Vec<usize> = (0..gene_count).collect();
+            self.breed(&parents, &everyone, remain_gene_count, rng, &mut next_generation_genes);
+        } else {
+            let min_size = self.evolution.speciation.min_species_size;
+            let counts = self.species.offspring_counts(fitness, remain_gene_count, min_size);
+            let members: Vec<Vec<usize>> = self.species.species.iter().map(|s| s.members.clone()).collect();
+            for (members, count) in members.iter().zip(counts) {
+                self.breed(&parents, members, count, rng, &mut next_generation_genes);
+            }
+        }
+
+        next_generation_genes
+    }
+
+    fn adopt(&mut self, population: &[Vec<f32>], rng: &mut StdRng) {
+        self.speciate(population, rng);
+    }
+
+    fn mutation_rate(&self, fitness: &[f32]) -> f32 {
+        let average = fitness.iter().sum::<f32>() / fitness.len() as f32;
+        self.evolution.mutation_rate.rate(average)
+    }
+
+    fn origin(&self, index: usize) -> Origin {
+        self.origins.get(index).copied().unwrap_or(Origin::Sampled)
+    }
+
+    fn species_of(&self, index: usize) -> Option<usize> {
+        self.species_ids.get(index).copied()
+    }
+
+    fn species_count(&self) -> usize {
+        if self.species_ids.is_empty() { 1 } else { self.species.species.len() }
+    }
+}
+
+/// Root mean square difference of two genomes.
+pub(crate) fn weight_distance(a: &[f32], b: &[f32]) -> f32 {
+    (a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum::<f32>() / a.len().max(1) as f32).sqrt()
+}
+
+#[cfg(test)]
+mod test{
+    use rand::SeedableRng;
+    use crate::resources::{Selection, SpeciationConfig};
+    use super::*;
+
+
+    #[test]
+    fn test_evolve(){
+        let algorithm = GeneticAlgorithm::new(&EvolutionConfig::default());
+        let genes = vec![1.0f32; 20];
+
+        let fitness: Vec<f32> = (0..100).map(|i| i as f32 * 0.2).collect();
+        let average = fitness.iter().sum::<f32>() / fitness.len() as f32;
+        let probability = 1.0f32 / f32::max(1.0, average);
+        let evolved = algorithm.evolve(&genes, probability, &mut StdRng::seed_from_u64(0));
+        assert_eq!(evolved.len(), genes.len());
+    }
+
+    #[test]
+    fn test_species_without_offspring_with_sus() {
+        let evolution = EvolutionConfig {
+            selection: Selection::Sus,
+            speciation: SpeciationConfig { enabled: true, threshold: 0.1, min_species_size: 0 },
+            ..EvolutionConfig::default()
+        };
+        let mut algorithm = GeneticAlgorithm::new(&evolution);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        // every genome is a species of its own, after the elite there are fewer children than species
+        let population: Vec<Vec<f32>> = (0..10).map(|i| vec![i as f32; 4]).collect();
+        let fitness: Vec<f32> = (0..10).map(|i| 1.0 + i as f32).collect();
+        algorithm.start(&population, &mut rng);
+        assert_eq!(algorithm.species_count(), 10);
+
+        let next = algorithm.next_generation(&population, &fitness, &NetworkShape::default(), &mut rng);
+        assert_eq!(next.len(), population.len());
+    }
+}
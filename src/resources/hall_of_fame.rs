@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HallOfFameEntry {
+    pub generation: usize,
+    pub fitness: f32,
+    /// Run seed the genome was scored under.
+    pub seed: u64,
+    pub genes: Vec<f32>,
+}
+
+/// The best genomes ever scored, best first, at most `capacity` of them.
+/// Kept across generations and checkpoints so a later regression can't lose them.
+#[derive(Debug, Clone, Default)]
+pub struct HallOfFame {
+    pub entries: Vec<HallOfFameEntry>,
+    capacity: usize,
+}
+
+impl HallOfFame {
+    pub fn new(capacity: usize) -> Self {
+        HallOfFame { entries: vec![], capacity }
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.entries.truncate(capacity);
+    }
+
+    /// Offers every scored genome of a generation. Genomes already in the archive keep their
+    /// first entry, so elites copied from one generation to the next don't fill it up.
+    pub fn record(&mut self, generation: usize, seed: u64, population: &[Vec<f32>], fitness: &[f32]) {
+        for (genes, &score) in population.iter().zip(fitness) {
+            if score <= 0.0 || genes.is_empty() {
+                continue;
+            }
+            if self.entries.len() >= self.capacity && self.entries.last().is_none_or(|worst| score <= worst.fitness) {
+                continue;
+            }
+            if self.entries.iter().any(|entry| entry.genes == *genes) {
+                continue;
+            }
+            let position = self.entries.partition_point(|entry| entry.fitness >= score);
+            self.entries.insert(position, HallOfFameEntry { generation, fitness: score, seed, genes: genes.clone() });
+            self.entries.truncate(self.capacity);
+        }
+    }
+
+    /// Replaces the archive with saved entries, keeping the best `capacity`.
+    pub fn restore(&mut self, mut entries: Vec<HallOfFameEntry>) {
+        entries.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+        entries.truncate(self.capacity);
+        self.entries = entries;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_keeps_best_unique_genomes() {
+        let mut hall_of_fame = HallOfFame::new(3);
+        let population: Vec<Vec<f32>> = (0..5).map(|i| vec![i as f32]).collect();
+        hall_of_fame.record(0, 7, &population, &[1.0, 5.0, 0.0, 3.0, 2.0]);
+        // the elite comes back unchanged with a different score, a new genome beats the worst entry
+        hall_of_fame.record(1, 7, &[vec![1.0], vec![9.0]], &[6.0, 4.0]);
+
+        let fitness: Vec<f32> = hall_of_fame.entries.iter().map(|e| e.fitness).collect();
+        assert_eq!(fitness, vec![5.0, 4.0, 3.0]);
+        assert_eq!(hall_of_fame.entries[1].generation, 1);
+    }
+}
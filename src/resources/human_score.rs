@@ -0,0 +1,65 @@
+use crate::components::BirdScore;
+
+/// Results of the bird flown by a person, kept across restarts.
+#[derive(Default)]
+pub struct HumanScore {
+    /// The last finished round.
+    pub last: Option<BirdScore>,
+    /// Most pipes passed, the longer flight on a tie.
+    pub best: Option<BirdScore>,
+}
+
+impl HumanScore {
+    pub fn record(&mut self, score: BirdScore) {
+        self.last = Some(score);
+        let better = self.best.is_none_or(|best| (score.pipes, score.time_alive) > (best.pipes, best.time_alive));
+        if better {
+            self.best = Some(score);
+        }
+    }
+}
+
+/// Pipes passed by the person and the best network in a race, see `GameMode::Race`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RaceStanding {
+    pub human_pipes: u32,
+    pub human_alive: bool,
+    /// Most pipes of any network bird, flying or crashed.
+    pub ai_pipes: u32,
+    pub ai_alive: usize,
+}
+
+impl RaceStanding {
+    /// Nothing the birds still flying do can change who wins.
+    pub fn is_decided(&self) -> bool {
+        match (self.human_alive, self.ai_alive > 0) {
+            (true, true) => false,
+            (true, false) => self.human_pipes > self.ai_pipes,
+            (false, true) => self.ai_pipes > self.human_pipes,
+            (false, false) => true,
+        }
+    }
+
+    pub fn verdict(&self) -> &'static str {
+        match self.human_pipes.cmp(&self.ai_pipes) {
+            std::cmp::Ordering::Greater => "You win",
+            std::cmp::Ordering::Less => "AI wins",
+            std::cmp::Ordering::Equal => "Tie",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_race_is_decided_once_the_leader_is_alone() {
+        let standing = RaceStanding { human_pipes: 3, human_alive: false, ai_pipes: 3, ai_alive: 2 };
+        assert!(!standing.is_decided());
+        assert!(RaceStanding { ai_pipes: 4, ..standing }.is_decided());
+        assert!(!RaceStanding { human_alive: true, ai_alive: 0, ..standing }.is_decided());
+        assert!(RaceStanding { human_alive: true, ai_alive: 0, ai_pipes: 2, ..standing }.is_decided());
+        assert_eq!(RaceStanding { ai_alive: 0, ..standing }.verdict(), "Tie");
+    }
+}
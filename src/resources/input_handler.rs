@@ -1,31 +1,38 @@
+#[cfg(feature = "render")]
 use winit::event::ElementState;
+#[cfg(feature = "render")]
 use winit::keyboard::{KeyCode, PhysicalKey};
 
+/// Jump requests of the human player, from the keyboard, mouse or touch.
+#[derive(Default)]
 pub struct InputHandler{
+    /// Set when the button goes down, cleared by `UpdatePlayer` once the bird jumped.
     pub jump: bool,
+    held: bool,
 }
 
-
-impl Default for InputHandler {
-    fn default() -> Self {
-        InputHandler {
-            jump: false
+impl InputHandler {
+    /// Holding the button, or key repeat, doesn't jump again.
+    pub fn press(&mut self) {
+        if !self.held {
+            self.jump = true;
         }
+        self.held = true;
     }
-}
 
+    pub fn release(&mut self) {
+        self.held = false;
+    }
+}
 
+#[cfg(feature = "render")]
 impl InputHandler {
     pub fn receive_keyboard_input(&mut self, state : ElementState, physical_key: PhysicalKey) -> bool {
         match physical_key {
             PhysicalKey::Code(KeyCode::Space) => {
                 match state {
-                    ElementState::Pressed => {
-                        self.jump = true;
-                    }
-                    ElementState::Released => {
-                        self.jump = false;
-                    }
+                    ElementState::Pressed => self.press(),
+                    ElementState::Released => self.release(),
                 }
                 true
             }
@@ -34,4 +41,4 @@ impl InputHandler {
             }
         }
     }
-}
\ No newline at end of file
+}
@@ -3,7 +3,24 @@ pub use delta_time::DeltaTime;
 pub use score::Score;
 pub use input_handler::InputHandler;
 pub use game_stage::GameFinished;
-pub use gene_handler::GeneHandler;
+pub use gene_handler::{Champion, GeneHandler, GenerationSummary, Lineage};
+pub use population_file::{GenomeRecord, PopulationFile};
+pub use network_file::NetworkFile;
+pub use sim_rng::CourseRng;
+pub use time_scale::TimeScale;
+pub use game_config::GameConfig;
+pub use network_shape::NetworkShape;
+pub use sensor_set::{Normalization, Observation, Sensor, SensorSet};
+pub use evolution_config::{EvolutionConfig, GenomeKind, SpeciationConfig};
+pub use selection::{Selection, SelectionStrategy};
+pub use operators::{Crossover, CrossoverOperator, Mutation, MutationOperator, MutationRate};
+pub use neat::{NeatConfig, NeatPopulation};
+pub use optimizer::{Optimizer, OptimizerKind, OptimizerState, Origin};
+pub use hall_of_fame::{HallOfFame, HallOfFameEntry};
+pub use stats_log::StatsLog;
+pub use fitness::{FitnessConfig, FitnessTerms};
+pub use human_score::{HumanScore, RaceStanding};
+pub use replay::{BirdRecord, ReplayFile, ReplayLog, REPLAY_FILE_VERSION};
 
 mod delta_time;
 mod input_handler;
@@ -11,5 +28,26 @@ mod camera;
 mod game_stage;
 mod score;
 mod gene_handler;
+mod population_file;
+mod network_file;
+pub mod sim_rng;
+mod time_scale;
+mod game_config;
+mod network_shape;
+pub mod sensor_set;
+mod evolution_config;
+mod selection;
+mod operators;
+mod species;
+pub mod neat;
+mod optimizer;
+mod genetic_algorithm;
+mod cma_es;
+mod nes;
+mod hall_of_fame;
+mod stats_log;
+mod fitness;
+mod human_score;
+mod replay;
 
 
@@ -0,0 +1,484 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+
+use crate::components::Activation;
+use crate::resources::operators::standard_normal;
+use crate::resources::optimizer::Origin;
+use crate::resources::species::SpeciesSet;
+
+/// NEAT settings, the `[evolution.neat]` table of the game config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NeatConfig {
+    /// Weights of excess genes, disjoint genes and the average weight difference in the compatibility distance.
+    pub excess_coefficient: f32,
+    pub disjoint_coefficient: f32,
+    pub weight_coefficient: f32,
+    /// Genomes closer than this to a species' representative join it.
+    pub compatibility_threshold: f32,
+    pub add_node_rate: f32,
+    pub add_connection_rate: f32,
+    /// Chance of each connection weight being changed, and of that change being a fresh weight.
+    pub weight_mutation_rate: f32,
+    pub weight_reset_rate: f32,
+    /// Standard deviation of weight perturbations.
+    pub weight_perturbation: f32,
+    /// New weights are drawn from `-weight_range..weight_range`.
+    pub weight_range: f32,
+    pub crossover_rate: f32,
+    /// Share of every species, best first, allowed to breed.
+    pub survival_ratio: f32,
+}
+
+impl Default for NeatConfig {
+    fn default() -> Self {
+        NeatConfig {
+            excess_coefficient: 1.0,
+            disjoint_coefficient: 1.0,
+            weight_coefficient: 0.4,
+            compatibility_threshold: 3.0,
+            add_node_rate: 0.03,
+            add_connection_rate: 0.05,
+            weight_mutation_rate: 0.8,
+            weight_reset_rate: 0.1,
+            weight_perturbation: 0.5,
+            weight_range: 2.0,
+            crossover_rate: 0.75,
+            survival_ratio: 0.2,
+        }
+    }
+}
+
+impl NeatConfig {
+    /// Applies one `evolution.neat.KEY=VALUE` override, with the prefix already stripped.
+    pub fn apply_override(&mut self, key: &str, value: &str) -> anyhow::Result<()> {
+        let value: f32 = value.parse()?;
+        match key {
+            "excess_coefficient" => self.excess_coefficient = value,
+            "disjoint_coefficient" => self.disjoint_coefficient = value,
+            "weight_coefficient" => self.weight_coefficient = value,
+            "compatibility_threshold" => self.compatibility_threshold = value,
+            "add_node_rate" => self.add_node_rate = value,
+            "add_connection_rate" => self.add_connection_rate = value,
+            "weight_mutation_rate" => self.weight_mutation_rate = value,
+            "weight_reset_rate" => self.weight_reset_rate = value,
+            "weight_perturbation" => self.weight_perturbation = value,
+            "weight_range" => self.weight_range = value,
+            "crossover_rate" => self.crossover_rate = value,
+            "survival_ratio" => self.survival_ratio = value,
+            other => anyhow::bail!("unknown config key evolution.neat.{}", other),
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let rates = [
+            ("add_node_rate", self.add_node_rate),
+            ("add_connection_rate", self.add_connection_rate),
+            ("weight_mutation_rate", self.weight_mutation_rate),
+            ("weight_reset_rate", self.weight_reset_rate),
+            ("crossover_rate", self.crossover_rate),
+            ("survival_ratio", self.survival_ratio),
+        ];
+        for (name, rate) in rates {
+            if !(0.0..=1.0).contains(&rate) {
+                anyhow::bail!("evolution.neat.{} must be in [0, 1]", name);
+            }
+        }
+        if self.weight_range <= 0.0 {
+            anyhow::bail!("evolution.neat.weight_range must be positive");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Input,
+    /// Always 1, so every node can learn an offset.
+    Bias,
+    Hidden,
+    Output,
+}
+
+#[derive(Debug, Clone)]
+pub struct NodeGene {
+    pub id: usize,
+    pub kind: NodeKind,
+    pub activation: Activation,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConnectionGene {
+    pub innovation: usize,
+    pub from: usize,
+    pub to: usize,
+    pub weight: f32,
+    pub enabled: bool,
+}
+
+/// Hands out the same innovation number to the same structural change anywhere in the population.
+#[derive(Default)]
+pub struct InnovationTracker {
+    connections: HashMap<(usize, usize), usize>,
+    splits: HashMap<usize, usize>,
+    next_node: usize,
+}
+
+impl InnovationTracker {
+    fn connection(&mut self, from: usize, to: usize) -> usize {
+        let next = self.connections.len();
+        *self.connections.entry((from, to)).or_insert(next)
+    }
+
+    /// Id of the node that splits connection `innovation`.
+    fn split(&mut self, innovation: usize) -> usize {
+        let next_node = &mut self.next_node;
+        *self.splits.entry(innovation).or_insert_with(|| {
+            *next_node += 1;
+            *next_node - 1
+        })
+    }
+}
+
+/// A genome of node and connection genes. Connections are kept sorted by innovation number and
+/// never form a cycle, so the network is always feed-forward.
+#[derive(Debug, Clone)]
+pub struct NeatGenome {
+    pub nodes: Vec<NodeGene>,
+    pub connections: Vec<ConnectionGene>,
+}
+
+impl NeatGenome {
+    /// Inputs and the bias connected straight to every output.
+    fn minimal(inputs: usize, outputs: usize, output_activation: Activation, config: &NeatConfig,
+               tracker: &mut InnovationTracker, rng: &mut StdRng) -> Self {
+        let mut nodes = vec![];
+        for id in 0..inputs {
+            nodes.push(NodeGene { id, kind: NodeKind::Input, activation: Activation::Identity });
+        }
+        nodes.push(NodeGene { id: inputs, kind: NodeKind::Bias, activation: Activation::Identity });
+        for id in inputs + 1..inputs + 1 + outputs {
+            nodes.push(NodeGene { id, kind: NodeKind::Output, activation: output_activation });
+        }
+        tracker.next_node = tracker.next_node.max(nodes.len());
+
+        let mut connections = vec![];
+        for to in inputs + 1..inputs + 1 + outputs {
+            for from in 0..=inputs {
+                connections.push(ConnectionGene {
+                    innovation: tracker.connection(from, to),
+                    from,
+                    to,
+                    weight: rng.gen_range(-config.weight_range..config.weight_range),
+                    enabled: true,
+                });
+            }
+        }
+        connections.sort_by_key(|c| c.innovation);
+        NeatGenome { nodes, connections }
+    }
+
+    /// Compatibility distance: `c1 * E / N + c2 * D / N + c3 * W`, with E excess and D disjoint genes,
+    /// W the average weight difference of matching genes and N the larger genome size (1 below 20 genes).
+    pub fn distance(&self, other: &NeatGenome, config: &NeatConfig) -> f32 {
+        let (a, b) = (&self.connections, &other.connections);
+        let (mut i, mut j) = (0, 0);
+        let (mut matching, mut weight_difference, mut disjoint) = (0, 0.0f32, 0);
+        while i < a.len() && j < b.len() {
+            match a[i].innovation.cmp(&b[j].innovation) {
+                std::cmp::Ordering::Equal => {
+                    matching += 1;
+                    weight_difference += (a[i].weight - b[j].weight).abs();
+                    i += 1;
+                    j += 1;
+                }
+                std::cmp::Ordering::Less => {
+                    disjoint += 1;
+                    i += 1;
+                }
+                std::cmp::Ordering::Greater => {
+                    disjoint += 1;
+                    j += 1;
+                }
+            }
+        }
+        let excess = (a.len() - i) + (b.len() - j);
+
+        let size = a.len().max(b.len());
+        let n = if size < 20 { 1.0 } else { size as f32 };
+        let average_weight = if matching > 0 { weight_difference / matching as f32 } else { 0.0 };
+        config.excess_coefficient * excess as f32 / n
+            + config.disjoint_coefficient * disjoint as f32 / n
+            + config.weight_coefficient * average_weight
+    }
+
+    /// Child of `self`, the fitter parent, and `other`. Matching genes come from either parent,
+    /// the rest from `self`, so the child has exactly `self`'s structure.
+    fn crossover(&self, other: &NeatGenome, rng: &mut StdRng) -> NeatGenome {
+        let other_genes: HashMap<usize, &ConnectionGene> = other.connections.iter().map(|c| (c.innovation, c)).collect();
+        let connections = self.connections.iter()
+            .map(|gene| match other_genes.get(&gene.innovation) {
+                Some(other_gene) => {
+                    let mut child = if rng.gen_bool(0.5) { gene.clone() } else { (*other_gene).clone() };
+                    child.enabled = (gene.enabled && other_gene.enabled) || rng.gen_bool(0.25);
+                    child
+                }
+                None => gene.clone(),
+            })
+            .collect();
+        NeatGenome { nodes: self.nodes.clone(), connections }
+    }
+
+    fn mutate(&mut self, config: &NeatConfig, hidden_activation: Activation, tracker: &mut InnovationTracker, rng: &mut StdRng) {
+        if rng.gen_bool(config.add_node_rate as f64) {
+            self.add_node(hidden_activation, tracker, rng);
+        }
+        if rng.gen_bool(config.add_connection_rate as f64) {
+            self.add_connection(config, tracker, rng);
+        }
+        for connection in self.connections.iter_mut() {
+            if rng.gen_bool(config.weight_mutation_rate as f64) {
+                if rng.gen_bool(config.weight_reset_rate as f64) {
+                    connection.weight = rng.gen_range(-config.weight_range..config.weight_range);
+                } else {
+                    connection.weight += standard_normal(rng) * config.weight_perturbation;
+                }
+            }
+        }
+    }
+
+    /// Splits an enabled connection with a new node, weighted 1 in and the old weight out.
+    fn add_node(&mut self, activation: Activation, tracker: &mut InnovationTracker, rng: &mut StdRng) {
+        let enabled: Vec<usize> = (0..self.connections.len()).filter(|i| self.connections[*i].enabled).collect();
+        if enabled.is_empty() {
+            return;
+        }
+        let split = enabled[rng.gen_range(0..enabled.len())];
+        let ConnectionGene { innovation, from, to, weight, .. } = self.connections[split].clone();
+        let id = tracker.split(innovation);
+        if self.nodes.iter().any(|n| n.id == id) {
+            return;
+        }
+
+        self.connections[split].enabled = false;
+        self.nodes.push(NodeGene { id, kind: NodeKind::Hidden, activation });
+        self.insert_connection(ConnectionGene { innovation: tracker.connection(from, id), from, to: id, weight: 1.0, enabled: true });
+        self.insert_connection(ConnectionGene { innovation: tracker.connection(id, to), from: id, to, weight, enabled: true });
+    }
+
+    /// Connects two unconnected nodes, as long as that doesn't close a cycle.
+    fn add_connection(&mut self, config: &NeatConfig, tracker: &mut InnovationTracker, rng: &mut StdRng) {
+        for _ in 0..20 {
+            let from = &self.nodes[rng.gen_range(0..self.nodes.len())];
+            let to = &self.nodes[rng.gen_range(0..self.nodes.len())];
+            if from.kind == NodeKind::Output || matches!(to.kind, NodeKind::Input | NodeKind::Bias) || from.id == to.id {
+                continue;
+            }
+            let (from, to) = (from.id, to.id);
+            if self.connections.iter().any(|c| c.from == from && c.to == to) || self.reaches(to, from) {
+                continue;
+            }
+            let weight = rng.gen_range(-config.weight_range..config.weight_range);
+            self.insert_connection(ConnectionGene { innovation: tracker.connection(from, to), from, to, weight, enabled: true });
+            return;
+        }
+    }
+
+    /// `true` if `target` can be reached from `start` over any connection, enabled or not.
+    fn reaches(&self, start: usize, target: usize) -> bool {
+        let mut stack = vec![start];
+        let mut visited = vec![];
+        while let Some(node) = stack.pop() {
+            if node == target {
+                return true;
+            }
+            if visited.contains(&node) {
+                continue;
+            }
+            visited.push(node);
+            stack.extend(self.connections.iter().filter(|c| c.from == node).map(|c| c.to));
+        }
+        false
+    }
+
+    fn insert_connection(&mut self, connection: ConnectionGene) {
+        let position = self.connections.partition_point(|c| c.innovation < connection.innovation);
+        self.connections.insert(position, connection);
+    }
+
+    /// Orders the nodes so every node comes after all of its inputs.
+    pub fn compile(&self) -> NeatNetwork {
+        let mut order: Vec<&NodeGene> = self.nodes.iter().filter(|n| matches!(n.kind, NodeKind::Input | NodeKind::Bias)).collect();
+        let input_count = order.len() - 1;
+        let mut remaining: Vec<&NodeGene> = self.nodes.iter().filter(|n| !matches!(n.kind, NodeKind::Input | NodeKind::Bias)).collect();
+        let enabled: Vec<&ConnectionGene> = self.connections.iter().filter(|c| c.enabled).collect();
+
+        while !remaining.is_empty() {
+            let ready = remaining.iter()
+                .position(|node| enabled.iter()
+                    .filter(|c| c.to == node.id)
+                    .all(|c| order.iter().any(|placed| placed.id == c.from)))
+                .expect("neat genome has a cycle");
+            order.push(remaining.remove(ready));
+        }
+
+        let position: HashMap<usize, usize> = order.iter().enumerate().map(|(i, n)| (n.id, i)).collect();
+        let nodes = order[input_count + 1..].iter()
+            .map(|node| {
+                let incoming = enabled.iter()
+                    .filter(|c| c.to == node.id)
+                    .map(|c| (position[&c.from], c.weight))
+                    .collect();
+                (node.activation, incoming)
+            })
+            .collect();
+        let mut outputs: Vec<&NodeGene> = order.iter().copied().filter(|n| n.kind == NodeKind::Output).collect();
+        outputs.sort_by_key(|n| n.id);
+
+        NeatNetwork {
+            input_count,
+            nodes,
+            outputs: outputs.iter().map(|n| position[&n.id]).collect(),
+        }
+    }
+}
+
+/// A `NeatGenome` flattened into evaluation order: the inputs, the bias, then every other node.
+#[derive(Debug, Clone)]
+pub struct NeatNetwork {
+    input_count: usize,
+    /// Activation and `(position, weight)` inputs of every node after the bias.
+    nodes: Vec<(Activation, Vec<(usize, f32)>)>,
+    outputs: Vec<usize>,
+}
+
+impl NeatNetwork {
+    pub fn evaluate(&self, input: &[f32]) -> Vec<f32> {
+        let mut values = Vec::with_capacity(self.input_count + 1 + self.nodes.len());
+        values.extend_from_slice(&input[..self.input_count]);
+        values.push(1.0);
+        for (activation, incoming) in &self.nodes {
+            let sum: f32 = incoming.iter().map(|(from, weight)| values[*from] * weight).sum();
+            values.push(activation.apply(sum));
+        }
+        self.outputs.iter().map(|output| values[*output]).collect()
+    }
+}
+
+/// A NEAT population with its innovation history and species.
+pub struct NeatPopulation {
+    pub genomes: Vec<NeatGenome>,
+    pub species: SpeciesSet<NeatGenome>,
+    species_ids: Vec<usize>,
+    /// Where every genome came from, see `Optimizer::origin`.
+    pub origins: Vec<Origin>,
+    tracker: InnovationTracker,
+    hidden_activation: Activation,
+}
+
+impl NeatPopulation {
+    pub fn new(size: usize, inputs: usize, outputs: usize, hidden_activation: Activation, output_activation: Activation,
+               config: &NeatConfig, rng: &mut StdRng) -> Self {
+        let mut tracker = InnovationTracker::default();
+        let genomes = (0..size)
+            .map(|_| NeatGenome::minimal(inputs, outputs, output_activation, config, &mut tracker, rng))
+            .collect();
+        let mut population = NeatPopulation {
+            genomes,
+            species: SpeciesSet::default(),
+            species_ids: vec![],
+            origins: vec![],
+            tracker,
+            hidden_activation,
+        };
+        population.speciate(config, rng);
+        population
+    }
+
+    /// Copies of the genomes at `indices`, without species or innovation history.
+    pub fn subset(&self, indices: &[usize]) -> NeatPopulation {
+        NeatPopulation {
+            genomes: indices.iter().map(|i| self.genomes[*i].clone()).collect(),
+            species: SpeciesSet::default(),
+            species_ids: vec![],
+            origins: vec![],
+            tracker: InnovationTracker::default(),
+            hidden_activation: self.hidden_activation,
+        }
+    }
+
+    fn speciate(&mut self, config: &NeatConfig, rng: &mut StdRng) {
+        self.species.speciate(&self.genomes, config.compatibility_threshold, |a, b| a.distance(b, config), rng);
+        self.species_ids = self.species.species_of(self.genomes.len());
+    }
+
+    pub fn species_of(&self, index: usize) -> Option<usize> {
+        self.species_ids.get(index).copied()
+    }
+
+    /// Breeds the next population from the scored one and speciates it. Every species gets children in
+    /// proportion to its shared fitness; species of five or more keep their best genome unchanged.
+    pub fn process_generation(&mut self, fitness: &[f32], config: &NeatConfig, rng: &mut StdRng) {
+        let counts = self.species.offspring_counts(fitness, self.genomes.len(), 0);
+
+        let mut next_generation = Vec::with_capacity(self.genomes.len());
+        self.origins.clear();
+        for (species, count) in self.species.species.iter().zip(counts) {
+            let mut members = species.members.clone();
+            members.sort_by(|a, b| fitness[*b].total_cmp(&fitness[*a]));
+
+            let mut count = count;
+            if members.len() >= 5 && count > 0 {
+                next_generation.push(self.genomes[members[0]].clone());
+                self.origins.push(Origin::Elite(members[0]));
+                count -= 1;
+            }
+
+            let parents = &members[..((members.len() as f32 * config.survival_ratio).ceil() as usize).clamp(1, members.len())];
+            for _ in 0..count {
+                let a = parents[rng.gen_range(0..parents.len())];
+                let (mut child, parent) = if parents.len() > 1 && rng.gen_bool(config.crossover_rate as f64) {
+                    let b = parents[rng.gen_range(0..parents.len())];
+                    let (fitter, other) = if fitness[a] >= fitness[b] { (a, b) } else { (b, a) };
+                    (self.genomes[fitter].crossover(&self.genomes[other], rng), fitter)
+                } else {
+                    (self.genomes[a].clone(), a)
+                };
+                child.mutate(config, self.hidden_activation, &mut self.tracker, rng);
+                next_generation.push(child);
+                self.origins.push(Origin::Offspring(parent));
+            }
+        }
+        self.genomes = next_generation;
+        self.speciate(config, rng);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use super::*;
+
+    #[test]
+    fn test_structural_mutations_stay_feed_forward() {
+        let config = NeatConfig { add_node_rate: 0.5, add_connection_rate: 0.5, ..Default::default() };
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut population = NeatPopulation::new(20, 2, 1, Activation::Tanh, Activation::Identity, &config, &mut rng);
+        assert_eq!(population.genomes[0].compile().evaluate(&[0.0, 0.0]).len(), 1);
+
+        for _ in 0..10 {
+            let fitness: Vec<f32> = (0..population.genomes.len()).map(|i| i as f32).collect();
+            population.process_generation(&fitness, &config, &mut rng);
+            assert_eq!(population.genomes.len(), 20);
+        }
+        assert!(population.genomes.iter().any(|g| g.nodes.len() > 4));
+        for genome in &population.genomes {
+            let output = genome.compile().evaluate(&[1.0, -1.0]);
+            assert!(output[0].is_finite());
+        }
+    }
+}
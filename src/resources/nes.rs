@@ -0,0 +1,102 @@
+use rand::rngs::StdRng;
+
+use crate::resources::operators::standard_normal;
+use crate::resources::optimizer::{ranked_best_first, best_genome, Optimizer, OptimizerState};
+use crate::resources::NetworkShape;
+
+/// Natural evolution strategies as in OpenAI's ES: the population is `mean ± sigma * noise` in
+/// antithetic pairs, and the mean follows the rank-weighted noise, an estimate of the fitness gradient.
+/// The first scored population, usually uniform random genomes, only picks the starting mean.
+pub struct Nes {
+    sigma: f32,
+    learning_rate: f32,
+    mean: Option<Vec<f32>>,
+}
+
+impl Nes {
+    pub fn new(sigma: f32, learning_rate: f32) -> Self {
+        Nes { sigma, learning_rate, mean: None }
+    }
+
+    fn update(&self, mean: &mut [f32], population: &[Vec<f32>], fitness: &[f32]) {
+        // centered ranks in -0.5..0.5 keep the step independent of the fitness scale
+        let mut shaped = vec![0.0f32; population.len()];
+        let last = (population.len() - 1).max(1) as f32;
+        for (rank, index) in ranked_best_first(fitness).iter().enumerate() {
+            shaped[*index] = 0.5 - rank as f32 / last;
+        }
+
+        let scale = self.learning_rate / (population.len() as f32 * self.sigma);
+        let mut gradient = vec![0.0f32; mean.len()];
+        for (genes, weight) in population.iter().zip(&shaped) {
+            for ((g, x), m) in gradient.iter_mut().zip(genes).zip(mean.iter()) {
+                // the sample's noise, times sigma
+                *g += weight * (x - m);
+            }
+        }
+        for (m, g) in mean.iter_mut().zip(&gradient) {
+            *m += scale * g;
+        }
+    }
+}
+
+impl Optimizer for Nes {
+    fn start(&mut self, _: &[Vec<f32>], _: &mut StdRng) {
+        self.mean = None;
+    }
+
+    fn state(&self) -> Option<OptimizerState> {
+        self.mean.clone().map(|mean| OptimizerState::Nes { mean })
+    }
+
+    fn resume(&mut self, state: OptimizerState) {
+        if let OptimizerState::Nes { mean } = state {
+            self.mean = Some(mean);
+        }
+    }
+
+    fn next_generation(&mut self, population: &[Vec<f32>], fitness: &[f32], _: &NetworkShape, rng: &mut StdRng) -> Vec<Vec<f32>> {
+        let mean = match self.mean.take() {
+            Some(mut mean) => {
+                self.update(&mut mean, population, fitness);
+                mean
+            }
+            None => best_genome(population, fitness).iter().map(|m| *m as f32).collect(),
+        };
+
+        let mut next = Vec::with_capacity(population.len());
+        while next.len() < population.len() {
+            let noise: Vec<f32> = mean.iter().map(|_| standard_normal(rng) * self.sigma).collect();
+            next.push(mean.iter().zip(&noise).map(|(m, e)| m + e).collect());
+            if next.len() < population.len() {
+                next.push(mean.iter().zip(&noise).map(|(m, e)| m - e).collect());
+            }
+        }
+        self.mean = Some(mean);
+        next
+    }
+
+    fn mutation_rate(&self, _: &[f32]) -> f32 {
+        self.sigma
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use super::*;
+
+    #[test]
+    fn test_nes_climbs_linear_fitness() {
+        let shape = NetworkShape::default();
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut population = vec![vec![0.0f32; 4]; 20];
+        let mut nes = Nes::new(0.1, 1.0);
+        for _ in 0..20 {
+            let fitness: Vec<f32> = population.iter().map(|x| x[0] - x[1]).collect();
+            population = nes.next_generation(&population, &fitness, &shape, &mut rng);
+        }
+        let mean = nes.mean.as_ref().unwrap();
+        assert!(mean[0] > 0.5 && mean[1] < -0.5, "mean {:?}", mean);
+    }
+}
@@ -0,0 +1,45 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::Activation;
+use crate::resources::SensorSet;
+
+pub const NETWORK_FILE_VERSION: u32 = 1;
+
+/// A single trained network, stored as JSON together with everything needed to rebuild it.
+/// `activations` holds one entry per weight layer, i.e. `layers.len() - 1` entries.
+/// Without `sensors` the network reads the default ones.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct NetworkFile {
+    pub version: u32,
+    pub layers: Vec<usize>,
+    #[serde(default)]
+    pub activations: Vec<Activation>,
+    #[serde(default)]
+    pub sensors: Option<SensorSet>,
+    pub generation: usize,
+    pub fitness: f32,
+    pub genes: Vec<f32>,
+}
+
+impl NetworkFile {
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let file: NetworkFile = serde_json::from_slice(&fs::read(path)?)?;
+        if file.version != NETWORK_FILE_VERSION {
+            anyhow::bail!("unsupported network file version {} (expected {})", file.version, NETWORK_FILE_VERSION);
+        }
+
+        let expected_genes: usize = file.layers.windows(2).map(|w| (w[0] + 1) * w[1]).sum();
+        if file.genes.len() != expected_genes {
+            anyhow::bail!("network file has {} genes, layers {:?} need {}", file.genes.len(), file.layers, expected_genes);
+        }
+        Ok(file)
+    }
+}
@@ -0,0 +1,66 @@
+use crate::components::Activation;
+use crate::game_configs::{HIDDEN_ACTIVATION, HIDDEN_LAYERS, NETWORK_OUTPUT_SIZE, OUTPUT_ACTIVATION};
+use crate::resources::SensorSet;
+
+/// Layer sizes of the fixed-topology network, input layer first, the activation of every
+/// layer after the input and the sensors feeding the input layer.
+/// Every layer is fully connected to the previous one with one bias per neuron,
+/// so a genome is `(previous + 1) * size` genes per layer, laid out layer by layer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkShape {
+    pub layers: Vec<usize>,
+    pub activations: Vec<Activation>,
+    pub sensors: SensorSet,
+}
+
+impl Default for NetworkShape {
+    fn default() -> Self {
+        NetworkShape::new(SensorSet::default(), &HIDDEN_LAYERS, NETWORK_OUTPUT_SIZE, HIDDEN_ACTIVATION, OUTPUT_ACTIVATION)
+    }
+}
+
+impl NetworkShape {
+    pub fn new(sensors: SensorSet, hidden_layers: &[usize], output_size: usize,
+               hidden_activation: Activation, output_activation: Activation) -> Self {
+        let mut layers = vec![sensors.len()];
+        layers.extend_from_slice(hidden_layers);
+        layers.push(output_size);
+
+        let mut activations = vec![hidden_activation; hidden_layers.len()];
+        activations.push(output_activation);
+        NetworkShape { layers, activations, sensors }
+    }
+
+    /// Shape stored in a file. Files without activations are purely linear,
+    /// files without sensors read the default ones.
+    pub fn from_layers(layers: Vec<usize>, activations: Vec<Activation>, sensors: Option<SensorSet>) -> anyhow::Result<Self> {
+        if layers.len() < 2 || layers.contains(&0) {
+            anyhow::bail!("invalid network layers {:?}", layers);
+        }
+        let activations = if activations.is_empty() {
+            vec![Activation::Identity; layers.len() - 1]
+        } else {
+            activations
+        };
+        if activations.len() + 1 != layers.len() {
+            anyhow::bail!("{} activations for {} layers", activations.len(), layers.len());
+        }
+        let sensors = sensors.unwrap_or_default();
+        if sensors.len() != layers[0] {
+            anyhow::bail!("{} sensors for an input layer of {}", sensors.len(), layers[0]);
+        }
+        Ok(NetworkShape { layers, activations, sensors })
+    }
+
+    pub fn input_size(&self) -> usize {
+        self.layers[0]
+    }
+
+    pub fn output_size(&self) -> usize {
+        *self.layers.last().unwrap()
+    }
+
+    pub fn gene_size(&self) -> usize {
+        self.layers.windows(2).map(|w| (w[0] + 1) * w[1]).sum()
+    }
+}
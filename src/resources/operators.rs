@@ -0,0 +1,348 @@
+use rand::Rng;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+
+use crate::game_configs::{EVOLVE_OFFSET, GENE_RANGE};
+use crate::resources::NetworkShape;
+
+/// Combines two parent genomes into a child.
+pub trait CrossoverOperator: Send + Sync {
+    fn cross(&self, a: &[f32], b: &[f32], shape: &NetworkShape, mutation_rate: f32, rng: &mut StdRng) -> Vec<f32>;
+
+    /// `true` if the child is already mutated and must not go through the mutation operator.
+    fn mutates(&self) -> bool {
+        false
+    }
+}
+
+/// Changes a genome in place, each gene with probability `rate`.
+pub trait MutationOperator: Send + Sync {
+    fn mutate(&self, genes: &mut [f32], rate: f32, rng: &mut StdRng);
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Crossover {
+    /// The original operator: single point, with every gene reset at the mutation rate
+    /// and the rest scaled by up to `1 + rate`. Children skip the mutation operator.
+    #[default]
+    Legacy,
+    SinglePoint,
+    /// Every gene from either parent with equal odds.
+    Uniform,
+    /// BLX-α, every gene drawn from the parents' range widened by `alpha` on both sides.
+    Blend { alpha: f32 },
+    /// Whole weight layers from either parent.
+    LayerWise,
+}
+
+impl Crossover {
+    pub fn build(&self) -> Box<dyn CrossoverOperator> {
+        match self {
+            Crossover::Legacy => Box::new(LegacyCrossover),
+            Crossover::SinglePoint => Box::new(SinglePointCrossover),
+            Crossover::Uniform => Box::new(UniformCrossover),
+            Crossover::Blend { alpha } => Box::new(BlendCrossover { alpha: *alpha }),
+            Crossover::LayerWise => Box::new(LayerWiseCrossover),
+        }
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        match self {
+            Crossover::Blend { alpha } if !(alpha.is_finite() && *alpha >= 0.0) => anyhow::bail!("blend alpha must not be negative"),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Written as `legacy`, `single_point`, `uniform`, `blend:ALPHA` or `layer_wise`.
+impl std::str::FromStr for Crossover {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, parameter) = split_parameter(s);
+        let crossover = match kind {
+            "legacy" => Crossover::Legacy,
+            "single_point" => Crossover::SinglePoint,
+            "uniform" => Crossover::Uniform,
+            "blend" => Crossover::Blend { alpha: parameter.map_or(Ok(0.5), str::parse)? },
+            "layer_wise" => Crossover::LayerWise,
+            _ => anyhow::bail!("unknown crossover {}", s),
+        };
+        crossover.validate()?;
+        Ok(crossover)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Mutation {
+    /// Adds uniform noise in `-offset..offset`.
+    Uniform { offset: f32 },
+    /// Adds normal noise with standard deviation `sigma`.
+    Gaussian { sigma: f32 },
+    /// Deb's polynomial mutation within the initial gene range, larger `eta` gives smaller steps.
+    Polynomial { eta: f32 },
+    /// Draws a fresh gene from the initial gene range.
+    Reset,
+}
+
+impl Default for Mutation {
+    fn default() -> Self {
+        Mutation::Uniform { offset: EVOLVE_OFFSET }
+    }
+}
+
+impl Mutation {
+    pub fn build(&self) -> Box<dyn MutationOperator> {
+        match self {
+            Mutation::Uniform { offset } => Box::new(UniformMutation { offset: *offset }),
+            Mutation::Gaussian { sigma } => Box::new(GaussianMutation { sigma: *sigma }),
+            Mutation::Polynomial { eta } => Box::new(PolynomialMutation { eta: *eta }),
+            Mutation::Reset => Box::new(ResetMutation),
+        }
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        match self {
+            Mutation::Uniform { offset } if !(offset.is_finite() && *offset > 0.0) => anyhow::bail!("uniform mutation offset must be positive"),
+            Mutation::Gaussian { sigma } if !(sigma.is_finite() && *sigma > 0.0) => anyhow::bail!("gaussian mutation sigma must be positive"),
+            Mutation::Polynomial { eta } if !(eta.is_finite() && *eta >= 0.0) => anyhow::bail!("polynomial mutation eta must not be negative"),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Written as `uniform:OFFSET`, `gaussian:SIGMA`, `polynomial:ETA` or `reset`.
+impl std::str::FromStr for Mutation {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, parameter) = split_parameter(s);
+        let mutation = match kind {
+            "uniform" => Mutation::Uniform { offset: parameter.map_or(Ok(EVOLVE_OFFSET), str::parse)? },
+            "gaussian" => Mutation::Gaussian { sigma: parameter.map_or(Ok(0.5), str::parse)? },
+            "polynomial" => Mutation::Polynomial { eta: parameter.map_or(Ok(20.0), str::parse)? },
+            "reset" => Mutation::Reset,
+            _ => anyhow::bail!("unknown mutation {}", s),
+        };
+        mutation.validate()?;
+        Ok(mutation)
+    }
+}
+
+/// Probability of each gene being mutated.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MutationRate {
+    /// `1 / average fitness` (at most 1), mutating less as the population improves.
+    #[default]
+    Adaptive,
+    Fixed { rate: f32 },
+}
+
+impl MutationRate {
+    pub fn rate(&self, average_fitness: f32) -> f32 {
+        match self {
+            MutationRate::Adaptive => 1.0f32 / f32::max(1.0, average_fitness),
+            MutationRate::Fixed { rate } => *rate,
+        }
+    }
+}
+
+/// Written as `adaptive` or a fixed rate like `0.05`.
+impl std::str::FromStr for MutationRate {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "adaptive" => Ok(MutationRate::Adaptive),
+            rate => Ok(MutationRate::Fixed { rate: rate.parse()? }),
+        }
+    }
+}
+
+fn split_parameter(s: &str) -> (&str, Option<&str>) {
+    match s.split_once(':') {
+        Some((kind, parameter)) => (kind, Some(parameter)),
+        None => (s, None),
+    }
+}
+
+struct LegacyCrossover;
+
+impl CrossoverOperator for LegacyCrossover {
+    fn cross(&self, a: &[f32], b: &[f32], _: &NetworkShape, mutation_rate: f32, rng: &mut StdRng) -> Vec<f32> {
+        let gene_size = a.len();
+        let mut gene = vec![0.0f32;gene_size];
+        let cross_point = rng.gen_range(0..gene_size);
+        for i in 0..gene_size {
+            let change : f32 = rng.gen_range(0.0..1.0);
+            if change < mutation_rate {
+                gene[i] = rng.gen_range(-GENE_RANGE..GENE_RANGE);
+            }
+            else {
+                let distrib = if mutation_rate > 0.0 { 1.0f32 + rng.gen_range(0.0f32..mutation_rate) } else { 1.0 };
+                gene[i] = if i < cross_point { a[i] } else { b[i] } * distrib;
+            }
+        }
+        gene
+    }
+
+    fn mutates(&self) -> bool {
+        true
+    }
+}
+
+struct SinglePointCrossover;
+
+impl CrossoverOperator for SinglePointCrossover {
+    fn cross(&self, a: &[f32], b: &[f32], _: &NetworkShape, _: f32, rng: &mut StdRng) -> Vec<f32> {
+        let cross_point = rng.gen_range(0..a.len());
+        a[..cross_point].iter().chain(&b[cross_point..]).cloned().collect()
+    }
+}
+
+struct UniformCrossover;
+
+impl CrossoverOperator for UniformCrossover {
+    fn cross(&self, a: &[f32], b: &[f32], _: &NetworkShape, _: f32, rng: &mut StdRng) -> Vec<f32> {
+        a.iter().zip(b).map(|(a, b)| if rng.gen_bool(0.5) { *a } else { *b }).collect()
+    }
+}
+
+struct BlendCrossover {
+    alpha: f32,
+}
+
+impl CrossoverOperator for BlendCrossover {
+    fn cross(&self, a: &[f32], b: &[f32], _: &NetworkShape, _: f32, rng: &mut StdRng) -> Vec<f32> {
+        a.iter().zip(b).map(|(a, b)| {
+            let (low, high) = (a.min(*b), a.max(*b));
+            let spread = (high - low) * self.alpha;
+            if high - low + 2.0 * spread <= f32::EPSILON {
+                return *a;
+            }
+            rng.gen_range(low - spread..high + spread)
+        }).collect()
+    }
+}
+
+struct LayerWiseCrossover;
+
+impl CrossoverOperator for LayerWiseCrossover {
+    fn cross(&self, a: &[f32], b: &[f32], shape: &NetworkShape, _: f32, rng: &mut StdRng) -> Vec<f32> {
+        let mut gene = Vec::with_capacity(a.len());
+        for layer in shape.layers.windows(2) {
+            let range = gene.len()..gene.len() + (layer[0] + 1) * layer[1];
+            let parent = if rng.gen_bool(0.5) { a } else { b };
+            gene.extend_from_slice(&parent[range]);
+        }
+        gene
+    }
+}
+
+struct UniformMutation {
+    offset: f32,
+}
+
+impl MutationOperator for UniformMutation {
+    fn mutate(&self, genes: &mut [f32], rate: f32, rng: &mut StdRng) {
+        for g in genes.iter_mut() {
+            let change : f32 = rng.gen_range(0.0..1.0);
+            if change < rate {
+                *g += rng.gen_range(-self.offset..self.offset);
+            }
+        }
+    }
+}
+
+struct GaussianMutation {
+    sigma: f32,
+}
+
+impl MutationOperator for GaussianMutation {
+    fn mutate(&self, genes: &mut [f32], rate: f32, rng: &mut StdRng) {
+        for g in genes.iter_mut() {
+            let change : f32 = rng.gen_range(0.0..1.0);
+            if change < rate {
+                *g += standard_normal(rng) * self.sigma;
+            }
+        }
+    }
+}
+
+/// Box-Muller transform.
+pub fn standard_normal(rng: &mut StdRng) -> f32 {
+    let u1: f32 = rng.gen_range(f32::EPSILON..1.0);
+    let u2: f32 = rng.gen_range(0.0..1.0);
+    (-2.0 * u1.ln()).sqrt() * (std::f32::consts::TAU * u2).cos()
+}
+
+struct PolynomialMutation {
+    eta: f32,
+}
+
+impl MutationOperator for PolynomialMutation {
+    fn mutate(&self, genes: &mut [f32], rate: f32, rng: &mut StdRng) {
+        let exponent = 1.0 / (self.eta + 1.0);
+        for g in genes.iter_mut() {
+            let change : f32 = rng.gen_range(0.0..1.0);
+            if change < rate {
+                let u: f32 = rng.gen_range(0.0..1.0);
+                let delta = if u < 0.5 {
+                    (2.0 * u).powf(exponent) - 1.0
+                } else {
+                    1.0 - (2.0 * (1.0 - u)).powf(exponent)
+                };
+                *g = (*g + delta * 2.0 * GENE_RANGE).clamp(-GENE_RANGE, GENE_RANGE);
+            }
+        }
+    }
+}
+
+struct ResetMutation;
+
+impl MutationOperator for ResetMutation {
+    fn mutate(&self, genes: &mut [f32], rate: f32, rng: &mut StdRng) {
+        for g in genes.iter_mut() {
+            let change : f32 = rng.gen_range(0.0..1.0);
+            if change < rate {
+                *g = rng.gen_range(-GENE_RANGE..GENE_RANGE);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use super::*;
+
+    #[test]
+    fn test_crossover_keeps_parent_genes() {
+        let shape = NetworkShape::default();
+        let a = vec![1.0; shape.gene_size()];
+        let b = vec![2.0; shape.gene_size()];
+        let mut rng = StdRng::seed_from_u64(1);
+        for crossover in ["single_point", "uniform", "layer_wise"] {
+            let child = crossover.parse::<Crossover>().unwrap().build().cross(&a, &b, &shape, 0.1, &mut rng);
+            assert_eq!(child.len(), a.len());
+            assert!(child.iter().all(|g| *g == 1.0 || *g == 2.0), "{} made new genes", crossover);
+        }
+        let child = Crossover::Blend { alpha: 0.5 }.build().cross(&a, &b, &shape, 0.1, &mut rng);
+        assert!(child.iter().all(|g| (0.5..2.5).contains(g)));
+    }
+
+    #[test]
+    fn test_invalid_parameters_are_rejected() {
+        for mutation in ["uniform:0", "uniform:-0.1", "gaussian:-1", "polynomial:-2"] {
+            assert!(mutation.parse::<Mutation>().is_err(), "{} was accepted", mutation);
+        }
+        assert!("blend:-0.5".parse::<Crossover>().is_err());
+
+        // configs from a file skip `from_str`
+        let evolution = crate::resources::EvolutionConfig { mutation: Mutation::Uniform { offset: 0.0 }, ..Default::default() };
+        assert!(evolution.validate().is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+
+use crate::resources::cma_es::{CmaEs, CmaState};
+use crate::resources::genetic_algorithm::GeneticAlgorithm;
+use crate::resources::nes::Nes;
+use crate::resources::{EvolutionConfig, NetworkShape};
+
+/// Turns a scored population of flat weight vectors (`DNA.genes`) into the next one.
+///
+/// `GeneHandler` calls `next_generation` once a round has ended, with the rng of that generation,
+/// and `start` whenever the population was replaced from outside: at startup or after a restore.
+pub trait Optimizer: Send + Sync {
+    fn start(&mut self, _population: &[Vec<f32>], _rng: &mut StdRng) {}
+
+    /// What the optimizer carries between generations besides the population, saved in checkpoints.
+    fn state(&self) -> Option<OptimizerState> {
+        None
+    }
+
+    /// Continues from the `state` of a checkpoint, after `start`. State of another optimizer is ignored.
+    fn resume(&mut self, _state: OptimizerState) {}
+
+    /// The next population, the same size as `population`.
+    fn next_generation(&mut self, population: &[Vec<f32>], fitness: &[f32], shape: &NetworkShape, rng: &mut StdRng) -> Vec<Vec<f32>>;
+
+    /// The next population as it will be flown: what `next_generation` returned, with reintroduced
+    /// hall of fame genomes in place of its tail.
+    fn adopt(&mut self, _population: &[Vec<f32>], _rng: &mut StdRng) {}
+
+    /// How strongly the next generation is varied, for the statistics log: the GA's mutation rate,
+    /// or the step size of the evolution strategies.
+    fn mutation_rate(&self, fitness: &[f32]) -> f32;
+
+    /// Where genome `index` of the last generation returned by `next_generation` came from.
+    fn origin(&self, _index: usize) -> Origin {
+        Origin::Sampled
+    }
+
+    /// Species of genome `index` when the optimizer groups the population, for tinting birds.
+    fn species_of(&self, _index: usize) -> Option<usize> {
+        None
+    }
+
+    fn species_count(&self) -> usize {
+        1
+    }
+}
+
+/// How a genome of a new generation came about, for tracking lineages.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Origin {
+    /// Copied unchanged from this genome of the previous generation.
+    Elite(usize),
+    /// Bred from this genome of the previous generation, the first or fitter parent of a crossover.
+    Offspring(usize),
+    /// Drawn without a single parent, like the samples of CMA-ES and NES.
+    Sampled,
+}
+
+/// Search distribution of the evolution strategies, which the population alone doesn't pin down.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OptimizerState {
+    CmaEs(CmaState),
+    Nes { mean: Vec<f32> },
+}
+
+impl OptimizerState {
+    pub fn gene_size(&self) -> usize {
+        match self {
+            OptimizerState::CmaEs(state) => state.gene_size(),
+            OptimizerState::Nes { mean } => mean.len(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OptimizerKind {
+    /// The genetic algorithm, configured by the rest of the `[evolution]` table.
+    #[default]
+    Ga,
+    /// Covariance matrix adaptation, starting with step size `sigma`.
+    CmaEs { sigma: f32 },
+    /// Natural evolution strategies with antithetic noise of size `sigma`.
+    Nes { sigma: f32, learning_rate: f32 },
+}
+
+impl OptimizerKind {
+    pub fn build(&self, evolution: &EvolutionConfig) -> Box<dyn Optimizer> {
+        match self {
+            OptimizerKind::Ga => Box::new(GeneticAlgorithm::new(evolution)),
+            OptimizerKind::CmaEs { sigma } => Box::new(CmaEs::new(*sigma)),
+            OptimizerKind::Nes { sigma, learning_rate } => Box::new(Nes::new(*sigma, *learning_rate)),
+        }
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        match self {
+            OptimizerKind::CmaEs { sigma } if *sigma <= 0.0 => anyhow::bail!("cma_es sigma must be positive"),
+            OptimizerKind::Nes { sigma, .. } if *sigma <= 0.0 => anyhow::bail!("nes sigma must be positive"),
+            OptimizerKind::Nes { learning_rate, .. } if *learning_rate <= 0.0 => anyhow::bail!("nes learning rate must be positive"),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Written as `ga`, `cma_es:SIGMA` or `nes:SIGMA:LEARNING_RATE`, parameters optional.
+impl std::str::FromStr for OptimizerKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let kind = parts.next().unwrap_or_default();
+        let mut parameter = |default: f32| parts.next().map_or(Ok(default), str::parse);
+        let optimizer = match kind {
+            "ga" => OptimizerKind::Ga,
+            "cma_es" => OptimizerKind::CmaEs { sigma: parameter(1.0)? },
+            "nes" => OptimizerKind::Nes { sigma: parameter(1.0)?, learning_rate: parameter(3.0)? },
+            _ => anyhow::bail!("unknown optimizer {}", s),
+        };
+        optimizer.validate()?;
+        Ok(optimizer)
+    }
+}
+
+/// Genome indices ordered from best to worst.
+pub(crate) fn ranked_best_first(fitness: &[f32]) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..fitness.len()).collect();
+    indices.sort_by(|a, b| fitness[*b].total_cmp(&fitness[*a]));
+    indices
+}
+
+/// Log-decreasing recombination weights for the best `mu` genomes, summing to 1.
+pub(crate) fn recombination_weights(mu: usize) -> Vec<f64> {
+    let raw: Vec<f64> = (1..=mu).map(|i| (mu as f64 + 0.5).ln() - (i as f64).ln()).collect();
+    let sum: f64 = raw.iter().sum();
+    raw.iter().map(|w| w / sum).collect()
+}
+
+/// The best genome, where the search distribution of CMA-ES and NES starts when it has no state yet.
+/// Averaging unrelated random genomes would only cancel them out.
+pub(crate) fn best_genome(population: &[Vec<f32>], fitness: &[f32]) -> Vec<f64> {
+    population[ranked_best_first(fitness)[0]].iter().map(|g| *g as f64).collect()
+}
@@ -0,0 +1,68 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::components::Activation;
+use crate::resources::{FitnessTerms, HallOfFameEntry, OptimizerState, SensorSet};
+
+pub const POPULATION_FILE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GenomeRecord {
+    pub fitness: f32,
+    pub genes: Vec<f32>,
+    #[serde(default)]
+    pub terms: FitnessTerms,
+}
+
+/// Whole `GeneHandler` population as written to disk.
+///
+/// Files ending in `.json` are stored as JSON, anything else uses the compact bincode form.
+/// Loading detects the format from the content, so either can be resumed from any path.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PopulationFile {
+    pub version: u32,
+    pub generation: usize,
+    pub seed: u64,
+    pub layers: Vec<usize>,
+    #[serde(default)]
+    pub activations: Vec<Activation>,
+    #[serde(default)]
+    pub sensors: Option<SensorSet>,
+    pub genomes: Vec<GenomeRecord>,
+    #[serde(default)]
+    pub hall_of_fame: Vec<HallOfFameEntry>,
+    /// Distribution of CMA-ES or NES, `None` for the genetic algorithm.
+    #[serde(default)]
+    pub optimizer: Option<OptimizerState>,
+}
+
+impl PopulationFile {
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let bytes = if is_json_path(path) {
+            serde_json::to_vec_pretty(self)?
+        } else {
+            bincode::serialize(self)?
+        };
+        fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let bytes = fs::read(path)?;
+        let file: PopulationFile = match bytes.iter().find(|b| !b.is_ascii_whitespace()) {
+            Some(b'{') => serde_json::from_slice(&bytes)?,
+            _ => bincode::deserialize(&bytes)?,
+        };
+
+        if file.version != POPULATION_FILE_VERSION {
+            anyhow::bail!("unsupported population file version {} (expected {})", file.version, POPULATION_FILE_VERSION);
+        }
+        Ok(file)
+    }
+}
+
+fn is_json_path(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
@@ -0,0 +1,191 @@
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::resources::GameConfig;
+
+pub const REPLAY_FILE_VERSION: u32 = 1;
+
+/// One round, stored as bincode: the course it was flown on and the ticks every bird jumped.
+/// Played back through the normal systems this reproduces the round exactly.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ReplayFile {
+    pub version: u32,
+    /// Run seed, the course is `CourseRng::for_round(seed, generation)`.
+    pub seed: u64,
+    pub generation: usize,
+    pub tick_rate: u32,
+    /// Length of the round, it may have been cut before every bird crashed.
+    pub ticks: u32,
+    #[serde(with = "json_text")]
+    pub config: GameConfig,
+    pub birds: Vec<BirdRecord>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct BirdRecord {
+    /// Genome of a network bird, `None` for the bird flown by a person.
+    pub genome: Option<usize>,
+    /// Fitness of the genome at the end of the round, 0 for the person's bird.
+    pub fitness: f32,
+    /// Ticks the bird jumped on, in order.
+    #[serde(with = "delta_varint")]
+    pub jumps: Vec<u32>,
+}
+
+impl ReplayFile {
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        fs::write(path, bincode::serialize(self)?)?;
+        Ok(())
+    }
+
+    /// Saves to `dir/generation-NNNNN.replay`, or `generation-NNNNN-2.replay` and up when the generation
+    /// was already recorded, by a restart of a person's round or an earlier run. Returns the path written.
+    pub fn save_new(&self, dir: &Path) -> anyhow::Result<PathBuf> {
+        let bytes = bincode::serialize(self)?;
+        for attempt in 1.. {
+            let path = match attempt {
+                1 => dir.join(format!("generation-{:05}.replay", self.generation)),
+                _ => dir.join(format!("generation-{:05}-{}.replay", self.generation, attempt)),
+            };
+            match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    file.write_all(&bytes)?;
+                    return Ok(path);
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        unreachable!()
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let bytes = fs::read(path)?;
+        if !bytes.starts_with(&REPLAY_FILE_VERSION.to_le_bytes()) {
+            anyhow::bail!("{} is not a version {} replay file", path.display(), REPLAY_FILE_VERSION);
+        }
+        Ok(bincode::deserialize(&bytes)?)
+    }
+}
+
+/// Jumps of every bird of the running round. `RecordReplay` writes them down,
+/// or `PlayReplay` makes the birds jump on them when `playing` is set.
+#[derive(Default)]
+pub struct ReplayLog {
+    /// Ticks run this round.
+    pub tick: u32,
+    /// One per bird, indexed by its `ReplaySlot`.
+    pub birds: Vec<BirdRecord>,
+    pub playing: bool,
+}
+
+impl ReplayLog {
+    pub fn jumps_at(&self, slot: usize, tick: u32) -> bool {
+        self.birds.get(slot).is_some_and(|bird| bird.jumps.binary_search(&tick).is_ok())
+    }
+}
+
+/// Jump ticks as LEB128 varints of the gap to the previous jump, about one byte per jump.
+mod delta_varint {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(ticks: &[u32], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut bytes = Vec::with_capacity(ticks.len());
+        let mut previous = 0;
+        for &tick in ticks {
+            let mut gap = tick - previous;
+            previous = tick;
+            while gap >= 0x80 {
+                bytes.push(gap as u8 | 0x80);
+                gap >>= 7;
+            }
+            bytes.push(gap as u8);
+        }
+        serializer.serialize_bytes(&bytes)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u32>, D::Error> {
+        let bytes = Vec::<u8>::deserialize(deserializer)?;
+        let mut ticks = vec![];
+        let (mut previous, mut gap, mut shift) = (0u32, 0u32, 0);
+        for byte in bytes {
+            gap |= ((byte & 0x7f) as u32) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                previous += gap;
+                ticks.push(previous);
+                gap = 0;
+                shift = 0;
+            }
+        }
+        Ok(ticks)
+    }
+}
+
+/// The config as JSON text, bincode can't read its internally tagged operator enums.
+mod json_text {
+    use serde::de::Error;
+    use serde::ser::Error as _;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::resources::GameConfig;
+
+    pub fn serialize<S: Serializer>(config: &GameConfig, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&serde_json::to_string(config).map_err(S::Error::custom)?)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<GameConfig, D::Error> {
+        serde_json::from_str(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay_file_round_trip() {
+        let file = ReplayFile {
+            version: REPLAY_FILE_VERSION,
+            seed: 7,
+            generation: 412,
+            tick_rate: 60,
+            ticks: 90_000,
+            config: GameConfig::default(),
+            birds: vec![
+                BirdRecord { genome: Some(3), fitness: 12.5, jumps: vec![0, 1, 127, 128, 300, 89_999] },
+                BirdRecord { genome: None, fitness: 0.0, jumps: vec![] },
+            ],
+        };
+        let bytes = bincode::serialize(&file).unwrap();
+        let loaded: ReplayFile = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(loaded.birds, file.birds);
+        assert_eq!(loaded.config, file.config);
+    }
+
+    #[test]
+    fn test_save_new_keeps_earlier_rounds() {
+        let dir = std::env::temp_dir().join(format!("replay_save_new_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let mut file = ReplayFile {
+            version: REPLAY_FILE_VERSION,
+            seed: 7,
+            generation: 3,
+            tick_rate: 60,
+            ticks: 10,
+            config: GameConfig::default(),
+            birds: vec![BirdRecord { genome: None, fitness: 0.0, jumps: vec![4] }],
+        };
+        let first = file.save_new(&dir).unwrap();
+        file.birds[0].jumps = vec![5];
+        let second = file.save_new(&dir).unwrap();
+        assert_eq!(first, dir.join("generation-00003.replay"));
+        assert_eq!(second, dir.join("generation-00003-2.replay"));
+        assert_eq!(ReplayFile::load(&first).unwrap().birds[0].jumps, vec![4]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
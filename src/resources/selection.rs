@@ -0,0 +1,245 @@
+use rand::Rng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+/// Picks parents for the next generation.
+///
+/// `prepare` runs once per generation with the fitness of every genome, then `pick` is called
+/// `picks` times, two picks per child.
+pub trait SelectionStrategy: Send + Sync {
+    fn prepare(&mut self, fitness: &[f32], picks: usize, rng: &mut StdRng);
+    fn pick(&mut self, rng: &mut StdRng) -> usize;
+}
+
+/// Selection strategy and its parameters, as written in the config.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Selection {
+    /// Fitness proportional, one spin of the wheel per pick.
+    #[default]
+    Roulette,
+    /// Best of `size` genomes drawn at random.
+    Tournament { size: usize },
+    /// Linear ranking, the best genome is `pressure` times as likely as the average one (1..2).
+    Rank { pressure: f32 },
+    /// Uniform among the best `ratio` of the population.
+    Truncation { ratio: f32 },
+    /// Stochastic universal sampling, fitness proportional with evenly spaced pointers.
+    Sus,
+}
+
+impl Selection {
+    pub fn build(&self) -> Box<dyn SelectionStrategy> {
+        match self {
+            Selection::Roulette => Box::new(Roulette::default()),
+            Selection::Tournament { size } => Box::new(Tournament { size: *size, fitness: vec![] }),
+            Selection::Rank { pressure } => Box::new(Rank { pressure: *pressure, ..Default::default() }),
+            Selection::Truncation { ratio } => Box::new(Truncation { ratio: *ratio, ..Default::default() }),
+            Selection::Sus => Box::new(Sus::default()),
+        }
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        match self {
+            Selection::Tournament { size } if *size == 0 => anyhow::bail!("tournament size must be positive"),
+            Selection::Rank { pressure } if !(1.0..=2.0).contains(pressure) => anyhow::bail!("rank pressure must be between 1 and 2"),
+            Selection::Truncation { ratio } if !(*ratio > 0.0 && *ratio <= 1.0) => anyhow::bail!("truncation ratio must be in (0, 1]"),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Written as `roulette`, `tournament:K`, `rank:PRESSURE`, `truncation:RATIO` or `sus`.
+impl std::str::FromStr for Selection {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, parameter) = match s.split_once(':') {
+            Some((kind, parameter)) => (kind, Some(parameter)),
+            None => (s, None),
+        };
+        let selection = match kind {
+            "roulette" => Selection::Roulette,
+            "tournament" => Selection::Tournament { size: parameter.map_or(Ok(3), str::parse)? },
+            "rank" => Selection::Rank { pressure: parameter.map_or(Ok(1.5), str::parse)? },
+            "truncation" => Selection::Truncation { ratio: parameter.map_or(Ok(0.3), str::parse)? },
+            "sus" => Selection::Sus,
+            _ => anyhow::bail!("unknown selection {}", s),
+        };
+        selection.validate()?;
+        Ok(selection)
+    }
+}
+
+/// Running sum of `weights`, for drawing proportionally to them.
+fn accumulate(weights: impl Iterator<Item=f32>) -> Vec<f32> {
+    weights.scan(0.0f32, |sum, w| {
+        *sum += w;
+        Some(*sum)
+    }).collect()
+}
+
+/// Index of the first accumulated weight above `value`. Rounding at the top of the sum can leave
+/// `value` at or above the last weight, which belongs to the last genome.
+fn find_accumulated(accumulated: &[f32], value: f32) -> usize {
+    accumulated.iter().position(|a| *a > value).unwrap_or(accumulated.len() - 1)
+}
+
+/// Genome indices ordered from worst to best.
+fn ranked(fitness: &[f32]) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..fitness.len()).collect();
+    indices.sort_by(|a, b| fitness[*a].total_cmp(&fitness[*b]));
+    indices
+}
+
+#[derive(Default)]
+struct Roulette {
+    accumulated: Vec<f32>,
+}
+
+impl SelectionStrategy for Roulette {
+    fn prepare(&mut self, fitness: &[f32], _: usize, _: &mut StdRng) {
+        self.accumulated = accumulate(fitness.iter().cloned());
+    }
+
+    fn pick(&mut self, rng: &mut StdRng) -> usize {
+        let max = *self.accumulated.last().unwrap();
+        if max <= 0.0 {
+            return rng.gen_range(0..self.accumulated.len());
+        }
+        find_accumulated(&self.accumulated, rng.gen_range(0.0..max))
+    }
+}
+
+struct Tournament {
+    size: usize,
+    fitness: Vec<f32>,
+}
+
+impl SelectionStrategy for Tournament {
+    fn prepare(&mut self, fitness: &[f32], _: usize, _: &mut StdRng) {
+        self.fitness = fitness.to_vec();
+    }
+
+    fn pick(&mut self, rng: &mut StdRng) -> usize {
+        (0..self.size)
+            .map(|_| rng.gen_range(0..self.fitness.len()))
+            .reduce(|best, i| if self.fitness[i] > self.fitness[best] { i } else { best })
+            .unwrap()
+    }
+}
+
+#[derive(Default)]
+struct Rank {
+    pressure: f32,
+    ranked: Vec<usize>,
+    accumulated: Vec<f32>,
+}
+
+impl SelectionStrategy for Rank {
+    fn prepare(&mut self, fitness: &[f32], _: usize, _: &mut StdRng) {
+        self.ranked = ranked(fitness);
+        let n = fitness.len() as f32;
+        let slope = if n > 1.0 { 2.0 * (self.pressure - 1.0) / (n - 1.0) } else { 0.0 };
+        self.accumulated = accumulate((0..fitness.len()).map(|rank| 2.0 - self.pressure + slope * rank as f32));
+    }
+
+    fn pick(&mut self, rng: &mut StdRng) -> usize {
+        let max = *self.accumulated.last().unwrap();
+        self.ranked[find_accumulated(&self.accumulated, rng.gen_range(0.0..max))]
+    }
+}
+
+#[derive(Default)]
+struct Truncation {
+    ratio: f32,
+    best: Vec<usize>,
+}
+
+impl SelectionStrategy for Truncation {
+    fn prepare(&mut self, fitness: &[f32], _: usize, _: &mut StdRng) {
+        let keep = ((fitness.len() as f32 * self.ratio).ceil() as usize).clamp(1, fitness.len());
+        self.best = ranked(fitness).into_iter().rev().take(keep).collect();
+    }
+
+    fn pick(&mut self, rng: &mut StdRng) -> usize {
+        self.best[rng.gen_range(0..self.best.len())]
+    }
+}
+
+#[derive(Default)]
+struct Sus {
+    fitness: Vec<f32>,
+    count: usize,
+    picks: Vec<usize>,
+}
+
+impl Sus {
+    /// One spin of the wheel with `count` evenly spaced pointers.
+    fn spin(&self, rng: &mut StdRng) -> Vec<usize> {
+        let accumulated = accumulate(self.fitness.iter().map(|f| f.max(0.0)));
+        let total = *accumulated.last().unwrap();
+        let mut picks: Vec<usize> = if total <= 0.0 {
+            (0..self.count).map(|i| i % self.fitness.len()).collect()
+        } else {
+            let spacing = total / self.count as f32;
+            let start = rng.gen_range(0.0..spacing);
+            (0..self.count).map(|i| find_accumulated(&accumulated, start + spacing * i as f32)).collect()
+        };
+        // pointers come out sorted, shuffle them so neighbours don't always pair up
+        picks.shuffle(rng);
+        picks
+    }
+}
+
+impl SelectionStrategy for Sus {
+    fn prepare(&mut self, fitness: &[f32], picks: usize, rng: &mut StdRng) {
+        self.fitness = fitness.to_vec();
+        self.count = picks;
+        self.picks.clear();
+        if picks > 0 {
+            self.picks = self.spin(rng);
+        }
+    }
+
+    fn pick(&mut self, rng: &mut StdRng) -> usize {
+        if self.picks.is_empty() {
+            // more picks than prepared for, spin the wheel again
+            self.count = self.count.max(1);
+            self.picks = self.spin(rng);
+        }
+        self.picks.pop().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use super::*;
+
+    #[test]
+    fn test_selection_prefers_fitter_genomes() {
+        let fitness: Vec<f32> = (0..100).map(|i| 1.0 + i as f32 * 1e-6).collect();
+        for selection in ["tournament:5", "rank:2.0", "truncation:0.2"] {
+            let mut strategy = selection.parse::<Selection>().unwrap().build();
+            let mut rng = StdRng::seed_from_u64(1);
+            strategy.prepare(&fitness, 1000, &mut rng);
+            let average = (0..1000).map(|_| strategy.pick(&mut rng)).sum::<usize>() as f32 / 1000.0;
+            assert!(average > 60.0, "{} picked an average index of {}", selection, average);
+        }
+    }
+
+    #[test]
+    fn test_sus_picks_beyond_prepared_count() {
+        let mut strategy = Selection::Sus.build();
+        let mut rng = StdRng::seed_from_u64(1);
+        strategy.prepare(&[1.0, 2.0, 3.0], 2, &mut rng);
+        assert!((0..5).all(|_| strategy.pick(&mut rng) < 3));
+    }
+
+    #[test]
+    fn test_rounding_past_the_top_picks_the_last_genome() {
+        assert_eq!(find_accumulated(&[1.0, 2.0, 3.0], 3.0), 2);
+    }
+}
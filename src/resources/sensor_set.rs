@@ -0,0 +1,285 @@
+use serde::{Deserialize, Serialize};
+
+use crate::components::{Pipe, PipeTarget, Transform};
+use crate::game_configs::{DEFAULT_SENSORS, RAY_LENGTH, WORLD_CEILING, WORLD_FLOOR};
+
+/// A single named network input, measured from the bird.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sensor {
+    /// Horizontal distance to the nearest pipe.
+    PipeDx,
+    /// Vertical distance to the nearest pipe's lower cap.
+    PipeDy,
+    /// Vertical distance to the top of the nearest pipe's gap.
+    GapTopDy,
+    /// Vertical velocity of the bird, `Player.force`.
+    Velocity,
+    NextPipeDx,
+    NextPipeDy,
+    /// Height above the floor.
+    Floor,
+    /// Distance below the ceiling.
+    Ceiling,
+    /// Distance to the first pipe or world bound along a ray at this angle in degrees,
+    /// 0 pointing forward and 90 up, divided by `RAY_LENGTH`. 1 when nothing is in range.
+    Ray(i16),
+}
+
+impl Sensor {
+    pub const ALL: [Sensor; 8] = [
+        Sensor::PipeDx, Sensor::PipeDy, Sensor::GapTopDy, Sensor::Velocity,
+        Sensor::NextPipeDx, Sensor::NextPipeDy, Sensor::Floor, Sensor::Ceiling,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Sensor::PipeDx => "pipe_dx",
+            Sensor::PipeDy => "pipe_dy",
+            Sensor::GapTopDy => "gap_top_dy",
+            Sensor::Velocity => "velocity",
+            Sensor::NextPipeDx => "next_pipe_dx",
+            Sensor::NextPipeDy => "next_pipe_dy",
+            Sensor::Floor => "floor",
+            Sensor::Ceiling => "ceiling",
+            Sensor::Ray(_) => "ray",
+        }
+    }
+
+    /// Typical magnitude of the raw value, used by `Normalization::Scale` and `Normalization::Tanh`.
+    fn scale(&self) -> f32 {
+        match self {
+            Sensor::PipeDx | Sensor::NextPipeDx => 16.0,
+            Sensor::Velocity => 10.0,
+            _ => WORLD_CEILING - WORLD_FLOOR,
+        }
+    }
+}
+
+impl std::str::FromStr for Sensor {
+    type Err = anyhow::Error;
+
+    /// Named sensors use their `name`, rays are written `ray:ANGLE`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(angle) = s.strip_prefix("ray:") {
+            return Ok(Sensor::Ray(angle.parse()?));
+        }
+        Sensor::ALL.iter()
+            .find(|sensor| sensor.name() == s)
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("unknown sensor {}", s))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Normalization {
+    /// Raw world units.
+    #[default]
+    None,
+    /// Divided by the sensor's typical magnitude, roughly -1..1.
+    Scale,
+    /// `Scale` squashed with tanh, strictly -1..1.
+    Tanh,
+}
+
+impl std::str::FromStr for Normalization {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Normalization::None),
+            "scale" => Ok(Normalization::Scale),
+            "tanh" => Ok(Normalization::Tanh),
+            _ => anyhow::bail!("unknown normalization {}", s),
+        }
+    }
+}
+
+/// The inputs of the network in order, and how they are normalized.
+/// Part of the network description, so saved networks read the same observations they were trained on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensorSet {
+    pub sensors: Vec<Sensor>,
+    #[serde(default)]
+    pub normalization: Normalization,
+}
+
+impl Default for SensorSet {
+    fn default() -> Self {
+        SensorSet {
+            sensors: DEFAULT_SENSORS.to_vec(),
+            normalization: Normalization::None,
+        }
+    }
+}
+
+impl SensorSet {
+    /// Parses a comma separated list of sensor names.
+    pub fn parse_sensors(list: &str) -> anyhow::Result<Vec<Sensor>> {
+        let sensors = list.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::parse)
+            .collect::<anyhow::Result<Vec<Sensor>>>()?;
+        if sensors.is_empty() {
+            anyhow::bail!("at least one sensor is needed");
+        }
+        Ok(sensors)
+    }
+
+    /// Replaces the ray sensors with one ray per angle, after the other sensors.
+    pub fn set_rays(&mut self, angles: &[i16]) {
+        self.sensors.retain(|sensor| !matches!(sensor, Sensor::Ray(_)));
+        self.sensors.extend(angles.iter().map(|angle| Sensor::Ray(*angle)));
+    }
+
+    pub fn len(&self) -> usize {
+        self.sensors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sensors.is_empty()
+    }
+
+    /// Network input for a bird at `position` moving with vertical `velocity`.
+    pub fn read(&self, observation: &Observation, position: [f32; 3], velocity: f32) -> Vec<f32> {
+        let [nearest, next] = &observation.pipes;
+        self.sensors.iter()
+            .map(|sensor| {
+                let value = match sensor {
+                    Sensor::PipeDx => nearest.x - position[0],
+                    Sensor::PipeDy => nearest.lower_y - position[1],
+                    Sensor::GapTopDy => nearest.gap_top - position[1],
+                    Sensor::Velocity => velocity,
+                    Sensor::NextPipeDx => next.x - position[0],
+                    Sensor::NextPipeDy => next.lower_y - position[1],
+                    Sensor::Floor => position[1] - WORLD_FLOOR,
+                    Sensor::Ceiling => WORLD_CEILING - position[1],
+                    Sensor::Ray(angle) => return observation.cast_ray([position[0], position[1]], *angle),
+                };
+                match self.normalization {
+                    Normalization::None => value,
+                    Normalization::Scale => value / sensor.scale(),
+                    Normalization::Tanh => (value / sensor.scale()).tanh(),
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PipeObservation {
+    pub x: f32,
+    /// Center of the lower cap, the `PipeTarget` entity.
+    pub lower_y: f32,
+    /// Bottom edge of the upper cap.
+    pub gap_top: f32,
+}
+
+impl PipeObservation {
+    /// Middle of the gap between the top of the lower cap and the upper cap.
+    pub fn gap_center(&self) -> f32 {
+        (self.lower_y + 1.0 + self.gap_top) * 0.5
+    }
+}
+
+impl Default for PipeObservation {
+    fn default() -> Self {
+        PipeObservation { x: 99.0, lower_y: 0.0, gap_top: 0.0 }
+    }
+}
+
+/// Pipe positions shared by every bird in a tick, nearest pipe first.
+#[derive(Debug, Clone, Default)]
+pub struct Observation {
+    pub pipes: [PipeObservation; 2],
+    /// Bounding box of every `Pipe` entity as `[min x, max x, min y, max y]`.
+    pub obstacles: Vec<[f32; 4]>,
+}
+
+impl Observation {
+    /// Collects the two nearest pipes whose lower cap hasn't passed the birds yet, and the bounds
+    /// of every pipe entity for the rays.
+    pub fn gather<'a>(pipes: impl Iterator<Item=(&'a Pipe, &'a Transform, Option<&'a PipeTarget>)>) -> Self {
+        let mut targets = vec![];
+        let mut upper_caps = vec![];
+        let mut obstacles = vec![];
+        for (pipe, transform, target) in pipes {
+            let [x, y, _] = transform.position;
+            let [half_width, half_height] = [transform.size[0] * 0.5, transform.size[1] * 0.5];
+            obstacles.push([x - half_width, x + half_width, y - half_height, y + half_height]);
+
+            if target.is_some() && transform.position[0] > -1.5 {
+                targets.push([transform.position[0], transform.position[1]]);
+            } else if pipe.pipe_index == 2 {
+                upper_caps.push([transform.position[0], transform.position[1] - transform.size[1] * 0.5]);
+            }
+        }
+        targets.sort_by(|a, b| a[0].total_cmp(&b[0]));
+
+        let mut observation = Observation { obstacles, ..Default::default() };
+        for (slot, target) in observation.pipes.iter_mut().zip(targets) {
+            let gap_top = upper_caps.iter()
+                .find(|cap| (cap[0] - target[0]).abs() < 0.01)
+                .map_or(target[1], |cap| cap[1]);
+            *slot = PipeObservation { x: target[0], lower_y: target[1], gap_top };
+        }
+        observation
+    }
+
+    /// Normalized distance from `origin` to the nearest obstacle or world bound along the ray.
+    pub fn cast_ray(&self, origin: [f32; 2], angle: i16) -> f32 {
+        let (dir_y, dir_x) = (angle as f32).to_radians().sin_cos();
+        let mut nearest = RAY_LENGTH;
+
+        for bound in [WORLD_FLOOR, WORLD_CEILING] {
+            if dir_y.abs() > f32::EPSILON {
+                let t = (bound - origin[1]) / dir_y;
+                if t >= 0.0 {
+                    nearest = nearest.min(t);
+                }
+            }
+        }
+
+        // slab test, the ray enters the box at the latest entry over both axes
+        for [min_x, max_x, min_y, max_y] in &self.obstacles {
+            let mut t_enter = 0.0f32;
+            let mut t_exit = nearest;
+            for (origin, dir, min, max) in [(origin[0], dir_x, *min_x, *max_x), (origin[1], dir_y, *min_y, *max_y)] {
+                if dir.abs() <= f32::EPSILON {
+                    if origin < min || origin > max {
+                        t_exit = -1.0;
+                    }
+                    continue;
+                }
+                let (t0, t1) = ((min - origin) / dir, (max - origin) / dir);
+                t_enter = t_enter.max(t0.min(t1));
+                t_exit = t_exit.min(t0.max(t1));
+            }
+            if t_enter <= t_exit {
+                nearest = t_enter;
+            }
+        }
+        nearest / RAY_LENGTH
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cast_ray() {
+        let observation = Observation {
+            obstacles: vec![[4.0, 6.0, -2.0, 2.0]],
+            ..Default::default()
+        };
+
+        assert_eq!(observation.cast_ray([0.0, 0.0], 0), 4.0 / RAY_LENGTH);
+        assert_eq!(observation.cast_ray([0.0, 0.0], 180), 1.0);
+        assert!((observation.cast_ray([0.0, 0.0], -90) - 7.0 / RAY_LENGTH).abs() < 1e-6);
+        assert!((observation.cast_ray([0.0, 4.0], 90) - 5.0 / RAY_LENGTH).abs() < 1e-6);
+        assert_eq!(observation.cast_ray([5.0, 0.0], 45), 0.0);
+    }
+}
@@ -0,0 +1,43 @@
+use rand::{Rng, SeedableRng, thread_rng};
+use rand::rngs::StdRng;
+
+pub const GENE_INIT_STREAM: u64 = 1;
+pub const GENE_EVOLVE_STREAM: u64 = 2;
+pub const COURSE_STREAM: u64 = 3;
+
+pub fn random_seed() -> u64 {
+    thread_rng().gen()
+}
+
+/// Mixes the run seed with a stream id and an index (usually the generation)
+/// so every random decision gets its own reproducible sequence.
+pub fn derive_seed(seed: u64, stream: u64, index: u64) -> u64 {
+    // splitmix64 finalizer
+    let mut z = seed
+        .wrapping_add(stream.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+        .wrapping_add(index.wrapping_mul(0xD1B5_4A32_D192_ED03));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Random source for pipe gap heights, reseeded at the start of every round.
+pub struct CourseRng(pub StdRng);
+
+impl Default for CourseRng {
+    fn default() -> Self {
+        CourseRng(StdRng::seed_from_u64(0))
+    }
+}
+
+impl CourseRng {
+    pub fn for_round(seed: u64, round: usize) -> Self {
+        CourseRng(StdRng::seed_from_u64(derive_seed(seed, COURSE_STREAM, round as u64)))
+    }
+
+    /// One of several independent courses of the same round, for `Evaluator`.
+    pub fn for_course(seed: u64, round: usize, course: usize) -> Self {
+        let round_seed = derive_seed(seed, COURSE_STREAM, round as u64);
+        CourseRng(StdRng::seed_from_u64(derive_seed(round_seed, COURSE_STREAM, course as u64)))
+    }
+}
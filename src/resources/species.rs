@@ -0,0 +1,117 @@
+use rand::Rng;
+use rand::rngs::StdRng;
+
+/// A group of similar genomes, compared against the `representative` picked last generation.
+pub struct Species<G> {
+    pub id: usize,
+    pub representative: G,
+    /// Indices into the population that was last speciated.
+    pub members: Vec<usize>,
+}
+
+/// Species that persist across generations, so ids stay stable while lineages survive.
+pub struct SpeciesSet<G> {
+    pub species: Vec<Species<G>>,
+    next_id: usize,
+}
+
+impl<G> Default for SpeciesSet<G> {
+    fn default() -> Self {
+        SpeciesSet { species: vec![], next_id: 0 }
+    }
+}
+
+impl<G: Clone> SpeciesSet<G> {
+    /// Puts every genome into the first species whose representative is closer than `threshold`,
+    /// or into a new species. Empty species are dropped and every species gets a new
+    /// representative from its members.
+    pub fn speciate(&mut self, population: &[G], threshold: f32, distance: impl Fn(&G, &G) -> f32, rng: &mut StdRng) {
+        for species in self.species.iter_mut() {
+            species.members.clear();
+        }
+
+        for (index, genome) in population.iter().enumerate() {
+            match self.species.iter_mut().find(|s| distance(&s.representative, genome) < threshold) {
+                Some(species) => species.members.push(index),
+                None => {
+                    self.species.push(Species { id: self.next_id, representative: genome.clone(), members: vec![index] });
+                    self.next_id += 1;
+                }
+            }
+        }
+
+        self.species.retain(|s| !s.members.is_empty());
+        for species in self.species.iter_mut() {
+            let pick = species.members[rng.gen_range(0..species.members.len())];
+            species.representative = population[pick].clone();
+        }
+    }
+
+    /// Species id of every genome of the last speciated population.
+    pub fn species_of(&self, population_size: usize) -> Vec<usize> {
+        let mut ids = vec![0; population_size];
+        for species in &self.species {
+            for member in &species.members {
+                ids[*member] = species.id;
+            }
+        }
+        ids
+    }
+
+    /// Fitness divided by the size of the genome's species (explicit fitness sharing).
+    pub fn shared_fitness(&self, fitness: &[f32]) -> Vec<f32> {
+        let mut shared = fitness.to_vec();
+        for species in &self.species {
+            for member in &species.members {
+                shared[*member] = fitness[*member] / species.members.len() as f32;
+            }
+        }
+        shared
+    }
+
+    /// Splits `total` children between the species in proportion to their summed shared fitness,
+    /// each species getting at least `min_size` when there is room for it.
+    pub fn offspring_counts(&self, fitness: &[f32], total: usize, min_size: usize) -> Vec<usize> {
+        let shared = self.shared_fitness(fitness);
+        let sums: Vec<f32> = self.species.iter()
+            .map(|s| s.members.iter().map(|m| shared[*m].max(0.0)).sum())
+            .collect();
+        let sum: f32 = sums.iter().sum();
+        let shares: Vec<f32> = sums.iter()
+            .map(|s| if sum > 0.0 { s / sum * total as f32 } else { total as f32 / sums.len() as f32 })
+            .collect();
+
+        let mut counts: Vec<usize> = shares.iter().map(|s| (s.floor() as usize).max(min_size)).collect();
+        let mut by_remainder: Vec<usize> = (0..shares.len()).collect();
+        by_remainder.sort_by(|a, b| (shares[*b] - shares[*b].floor()).total_cmp(&(shares[*a] - shares[*a].floor())));
+        let mut next = by_remainder.iter().cycle();
+        while counts.iter().sum::<usize>() < total {
+            counts[*next.next().unwrap()] += 1;
+        }
+        while counts.iter().sum::<usize>() > total {
+            let largest = (0..counts.len()).max_by_key(|i| counts[*i]).unwrap();
+            counts[largest] -= 1;
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use super::*;
+
+    #[test]
+    fn test_offspring_counts() {
+        let population = vec![0.0f32, 0.1, 10.0, 10.1, 10.2, 20.0];
+        let fitness = vec![1.0, 1.0, 1.0, 1.0, 1.0, 4.0];
+        let mut species = SpeciesSet::default();
+        species.speciate(&population, 1.0, |a, b| (a - b).abs(), &mut StdRng::seed_from_u64(0));
+        assert_eq!(species.species.len(), 3);
+
+        // sharing: every species sums to 1 except the lone 4.0
+        assert_eq!(species.offspring_counts(&fitness, 12, 0), vec![2, 2, 8]);
+        assert_eq!(species.offspring_counts(&fitness, 12, 3), vec![3, 3, 6]);
+        assert_eq!(species.offspring_counts(&fitness, 6, 0).iter().sum::<usize>(), 6);
+    }
+}
@@ -0,0 +1,94 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use crate::resources::GenerationSummary;
+
+const CSV_HEADER: &str = "generation,best_fitness,average_fitness,worst_fitness,median_fitness,fitness_std_dev,\
+best_pipes,average_pipes,average_gap_distance,average_wasted_jumps,diversity,mutation_rate,species,best_index,wall_time";
+
+/// Training statistics, one line per generation, for plotting learning curves.
+///
+/// Files ending in `.csv` get a header and comma separated rows, anything else is written as JSON Lines.
+/// Every line goes straight to the file, so a run can be plotted while it trains.
+pub struct StatsLog {
+    file: File,
+    csv: bool,
+}
+
+impl StatsLog {
+    /// Starts the log at `path`, replacing the file, or with `resume` appends to it so a resumed run
+    /// continues the curve of the run it resumes. A CSV log is only appended to under the same header.
+    pub fn open(path: &Path, resume: bool) -> anyhow::Result<Self> {
+        let csv = path.extension().is_some_and(|extension| extension == "csv");
+        let existing = if resume { fs::read_to_string(path).unwrap_or_default() } else { String::new() };
+        if csv && !existing.is_empty() && existing.lines().next() != Some(CSV_HEADER) {
+            anyhow::bail!("{} has other columns than this version writes, use another --stats path", path.display());
+        }
+
+        let mut file = OpenOptions::new().create(true).write(true).append(resume).truncate(!resume).open(path)?;
+        if csv && existing.is_empty() {
+            writeln!(file, "{}", CSV_HEADER)?;
+        }
+        Ok(StatsLog { file, csv })
+    }
+
+    pub fn append(&mut self, summary: &GenerationSummary) -> anyhow::Result<()> {
+        if self.csv {
+            writeln!(self.file, "{}", csv_row(summary))?;
+        } else {
+            writeln!(self.file, "{}", serde_json::to_string(summary)?)?;
+        }
+        Ok(())
+    }
+}
+
+fn csv_row(s: &GenerationSummary) -> String {
+    format!("{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            s.generation, s.best_fitness, s.average_fitness, s.worst_fitness, s.median_fitness, s.fitness_std_dev,
+            s.best_pipes, s.average_pipes, s.average_gap_distance, s.average_wasted_jumps, s.diversity, s.mutation_rate,
+            s.species, s.best_index, s.wall_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::resources::{GeneHandler, NetworkShape};
+    use super::*;
+
+    #[test]
+    fn test_csv_row_matches_header() {
+        let summary = GeneHandler::new(1, NetworkShape::default()).summarize();
+        assert_eq!(csv_row(&summary).split(',').count(), CSV_HEADER.split(',').count());
+    }
+
+    /// Writes `summary` in a first run and in a second one, returning the lines of the log.
+    fn write_runs(name: &str, resume: bool, summary: &GenerationSummary) -> Vec<String> {
+        let path = std::env::temp_dir().join(format!("{}_{}.csv", name, std::process::id()));
+        StatsLog::open(&path, false).unwrap().append(summary).unwrap();
+        StatsLog::open(&path, resume).unwrap().append(summary).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_fresh_run_replaces_log() {
+        let summary = GeneHandler::new(1, NetworkShape::default()).summarize();
+        assert_eq!(write_runs("stats_log_fresh", false, &summary), [CSV_HEADER.to_string(), csv_row(&summary)]);
+    }
+
+    #[test]
+    fn test_resumed_run_appends_to_log() {
+        let summary = GeneHandler::new(1, NetworkShape::default()).summarize();
+        assert_eq!(write_runs("stats_log_resumed", true, &summary), [CSV_HEADER.to_string(), csv_row(&summary), csv_row(&summary)]);
+    }
+
+    #[test]
+    fn test_resumed_run_rejects_other_header() {
+        let path = std::env::temp_dir().join(format!("stats_log_header_{}.csv", std::process::id()));
+        fs::write(&path, "generation,best_fitness\n0,1.0\n").unwrap();
+        let result = StatsLog::open(&path, true);
+        fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,34 @@
+/// How fast the simulation runs compared to real time.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum TimeScale {
+    #[default]
+    X1,
+    X2,
+    X8,
+    X64,
+    /// As many ticks as fit into one rendered frame.
+    Max,
+}
+
+impl TimeScale {
+    /// Real-time multiplier, `None` for `Max`.
+    pub fn factor(&self) -> Option<f32> {
+        match self {
+            TimeScale::X1 => Some(1.0),
+            TimeScale::X2 => Some(2.0),
+            TimeScale::X8 => Some(8.0),
+            TimeScale::X64 => Some(64.0),
+            TimeScale::Max => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TimeScale::X1 => "1x",
+            TimeScale::X2 => "2x",
+            TimeScale::X8 => "8x",
+            TimeScale::X64 => "64x",
+            TimeScale::Max => "max",
+        }
+    }
+}
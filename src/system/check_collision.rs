@@ -1,7 +1,8 @@
 use specs::{Entities, Join, Read, ReadStorage, System, Write};
 
-use crate::components::{DNA, Pipe, Player, Transform};
-use crate::resources::{GeneHandler, Score};
+use crate::components::{BirdScore, DNA, Human, Pipe, PipeTarget, Transform};
+use crate::game_configs::{WORLD_CEILING, WORLD_FLOOR};
+use crate::resources::{FitnessTerms, GameConfig, GeneHandler, HumanScore, Observation};
 
 pub struct CheckCollision;
 
@@ -12,24 +13,25 @@ pub struct CheckCollision;
 impl<'a> System<'a> for CheckCollision {
     type SystemData = (
         Entities<'a>,
-        ReadStorage<'a, Player>,
+        ReadStorage<'a, BirdScore>,
         ReadStorage<'a, Pipe>,
+        ReadStorage<'a, PipeTarget>,
         ReadStorage<'a, Transform>,
 
         //todo 이걸 여기서 해야 할까?
         ReadStorage<'a, DNA>,
+        ReadStorage<'a, Human>,
         Write<'a, GeneHandler>,
-        Read<'a, Score>
+        Write<'a, HumanScore>,
+        Read<'a, GameConfig>
     );
 
-    fn run(&mut self, (entities,players, pipes, transforms, dna,mut gene_handler, score): Self::SystemData) {
+    fn run(&mut self, (entities, scores, pipes, pipe_targets, transforms, dna, humans, mut gene_handler, mut human_score, config): Self::SystemData) {
+        let next_pipe = Observation::gather((&pipes, &transforms, pipe_targets.maybe()).join()).pipes[0];
 
-        for ( e, _, player_tr, d) in  (&entities, &players, &transforms, &dna).join() {
+        for ( e, score, player_tr, d, human) in  (&entities, &scores, &transforms, dna.maybe(), humans.maybe()).join() {
             let pt =player_tr.position;
-            if pt[1] < -7.0  || pt[1] > 9.0{
-                gene_handler.set_score(d.index , score.0);
-                entities.delete(e).expect("delete player fail!!!");
-            }
+            let mut dead = pt[1] < WORLD_FLOOR  || pt[1] > WORLD_CEILING;
             for (_, pipe_tr) in  ( & pipes, &transforms).join() {
                 let obstacle_point = [
                     if pt[0] > pipe_tr.position[0] + pipe_tr.size[0] * 0.5 {
@@ -54,12 +56,24 @@ impl<'a> System<'a> for CheckCollision {
 
                 let dist_pow = (obstacle_point[0] - pt[0]) * (obstacle_point[0] - pt[0]) + (obstacle_point[1] - pt[1]) * (obstacle_point[1] - pt[1]);
                 if dist_pow < 0.2 {
-                    gene_handler.set_score(d.index , score.0);
-                    entities.delete(e).expect("delete player fail!!!");
-                    continue;
+                    dead = true;
                 }
 
             }
+
+            if !dead {
+                continue;
+            }
+            if let Some(d) = d {
+                let terms = FitnessTerms::of_bird(score, pt[1], &next_pipe);
+                gene_handler.set_score(d.index , config.fitness.score(&terms));
+                gene_handler.set_terms(d.index, terms);
+            }
+            // replayed birds have neither, their scores are already in the replay file
+            if human.is_some() {
+                human_score.record(*score);
+            }
+            entities.delete(e).expect("delete player fail!!!");
         }
     }
 }
\ No newline at end of file
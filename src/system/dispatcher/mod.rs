@@ -24,7 +24,10 @@ construct_dispatcher!(
     (ScrollBackground, "update_scroll", &[]),
     (UpdatePipe, "update_pipe", &[]),
     (ProcessNN, "process_nn", &[]),
-    (UpdatePlayer, "update_player", &[]),
+    (PlayReplay, "play_replay", &[]),
+    (UpdatePlayer, "update_player", &["play_replay"]),
+    (RecordReplay, "record_replay", &["update_player"]),
+    (UpdateBirdScore, "update_bird_score", &[]),
     (CheckCollision, "check_collision", &[]),
     (CheckGameStage , "check_game_stage" , &[]),
     (UpdateAnimation , "update_animation" , &[])
@@ -6,7 +6,9 @@ pub use scroll_pipe::UpdatePipe;
 pub use update_player::UpdatePlayer;
 pub use check_game_stage::CheckGameStage;
 pub use update_animation::UpdateAnimation;
-pub use process_nn::ProcessNN;
+pub use update_bird_score::UpdateBirdScore;
+pub use replay::{PlayReplay, RecordReplay};
+pub use process_nn::{ProcessNN, process_network, process_network_layers};
 
 
 mod update_camera;
@@ -17,6 +19,8 @@ mod scroll_pipe;
 mod update_player;
 mod check_game_stage;
 mod update_animation;
+mod update_bird_score;
+mod replay;
 mod process_nn;
 
 
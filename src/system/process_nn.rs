@@ -1,5 +1,6 @@
-use specs::{Join, ReadStorage, System, WriteStorage};
-use crate::components::{DNA, PipeTarget, Player, Transform};
+use specs::{Join, Read, ReadStorage, System, WriteStorage};
+use crate::components::{DNA, Pipe, PipeTarget, Player, Transform};
+use crate::resources::{NetworkShape, Observation};
 
 pub struct ProcessNN;
 
@@ -31,51 +32,50 @@ fn process_layer(input_array: Vec<f32>, genes: Vec<&f32>, neuron_count: usize) -
     return res;
 }
 
+/// Feeds `input` through every layer of `shape` and returns the activated values of all layers,
+/// input layer first.
+pub fn process_network_layers(shape: &NetworkShape, genes: &[f32], input: Vec<f32>) -> Vec<Vec<f32>> {
+    let mut values = vec![input];
+    let mut offset = 0;
+    for (layer, activation) in shape.layers.windows(2).zip(shape.activations.iter()) {
+        let layer_gene_size = (layer[0] + 1) * layer[1];
+        let layer_gene = genes.iter().skip(offset).take(layer_gene_size).collect();
+        let mut output = process_layer(values.last().unwrap().clone(), layer_gene, layer[1]);
+        output.iter_mut().for_each(|v| *v = activation.apply(*v));
+        values.push(output);
+        offset += layer_gene_size;
+    }
+    values
+}
+
+/// Feeds `input` through every layer of `shape` and returns the output layer.
+pub fn process_network(shape: &NetworkShape, genes: &[f32], input: Vec<f32>) -> Vec<f32> {
+    process_network_layers(shape, genes, input).pop().unwrap()
+}
+
 
 impl<'a> System<'a> for ProcessNN {
     type SystemData = (
+        ReadStorage<'a, Pipe>,
         ReadStorage<'a, PipeTarget>,
         ReadStorage<'a, Transform>,
         WriteStorage<'a, Player>,
         ReadStorage<'a, DNA>,
+        Read<'a, NetworkShape>,
     );
 
-    fn run(&mut self, (pipe, transform, mut pl, dna): Self::SystemData) {
-        let mut pipe_position = [99.0, 0.0];
-
-        for (_, pipe_tr) in (&pipe, &transform).join() {
-            if pipe_tr.position[0] > -1.5 &&  pipe_position[0] > pipe_tr.position[0] {
-                pipe_position = [pipe_tr.position[0], pipe_tr.position[1]];
-            }
-        }
-        // log::info!("{:?}" , pipe_position);
+    fn run(&mut self, (pipe, pipe_target, transform, mut pl, dna, shape): Self::SystemData) {
+        let observation = Observation::gather((&pipe, &transform, pipe_target.maybe()).join());
 
         for (pl, p_tr, gene) in (&mut pl, &transform, &dna).join() {
-            let input_data = vec![
-                pipe_position[0] - p_tr.position[0],
-                pipe_position[1] - p_tr.position[1],
-            ];
-
-            let input_data_size = 2;
-
-            let hidden_layer_1_size = gene.hidden_layers[0];
-            let layer_1_gene_size = (input_data_size + 1) * hidden_layer_1_size;
-            let gene_layer_1 = gene.genes.iter().take(layer_1_gene_size).collect();
-            let layer_1 = process_layer(input_data ,gene_layer_1, hidden_layer_1_size );
-
-            let hidden_layer_2_size = gene.hidden_layers[1];
-            let layer_2_gene_size =  (hidden_layer_1_size + 1)  * hidden_layer_2_size;
-            let layer_2_gene = gene.genes.iter().skip(layer_1_gene_size).take(layer_2_gene_size).collect();
-            let layer_2 = process_layer(layer_1 ,layer_2_gene, hidden_layer_2_size );
+            let input_data = shape.sensors.read(&observation, p_tr.position, pl.force);
 
+            let output_layer = match &gene.graph {
+                Some(graph) => graph.evaluate(&input_data),
+                None => process_network(&shape, &gene.genes, input_data),
+            };
 
-
-            let output_layer_gene_size= hidden_layer_2_size + 1;
-            let output_layer_gene = gene.genes.iter().skip(layer_1_gene_size + layer_2_gene_size).take(output_layer_gene_size).collect();
-            let output_layer = process_layer(layer_2 ,output_layer_gene, 1 );
-
-
-            pl.jump = output_layer[0] > 0.0f32;
+            pl.jump = output_layer[0] > shape.activations.last().unwrap().threshold();
         }
     }
 }
@@ -122,4 +122,20 @@ mod tests {
         assert!(approximately_equal(res[3] , 0.18f32));
         assert!(approximately_equal(res[4] , 0.05f32));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_process_network_activations(){
+        use crate::components::Activation;
+        use crate::resources::{Sensor, SensorSet};
+        let sensors = SensorSet { sensors: vec![Sensor::Velocity], ..Default::default() };
+        let shape = NetworkShape::new(sensors, &[2], 1, Activation::Relu, Activation::Tanh);
+        let genes = vec![
+            1.0, 0.0,
+            -1.0, 0.0,
+            1.0, 1.0, 0.5];
+
+        let values = process_network_layers(&shape, &genes, vec![2.0]);
+        assert_eq!(values[1], vec![2.0, 0.0]);
+        assert!(approximately_equal(values[2][0], 2.5f32.tanh()));
+    }
+}
@@ -0,0 +1,122 @@
+use specs::{Join, Read, ReadStorage, System, Write, WriteStorage};
+
+use crate::components::{BirdScore, Player, ReplaySlot};
+use crate::resources::ReplayLog;
+
+/// Makes the birds of a replay jump on the ticks they jumped on when the round was recorded.
+pub struct PlayReplay;
+
+impl<'a> System<'a> for PlayReplay {
+    type SystemData = (
+        WriteStorage<'a, Player>,
+        ReadStorage<'a, ReplaySlot>,
+        Read<'a, ReplayLog>
+    );
+
+    fn run(&mut self, (mut players, slots, log): Self::SystemData) {
+        if !log.playing {
+            return;
+        }
+        for (player, slot) in (&mut players, &slots).join() {
+            if log.jumps_at(slot.0, log.tick) {
+                player.jump = true;
+            }
+        }
+    }
+}
+
+/// Writes down the ticks every bird jumped on, after `UpdatePlayer` counted the jumps of
+/// networks and people alike, and moves the replay on by one tick.
+pub struct RecordReplay;
+
+impl<'a> System<'a> for RecordReplay {
+    type SystemData = (
+        ReadStorage<'a, BirdScore>,
+        ReadStorage<'a, ReplaySlot>,
+        Write<'a, ReplayLog>
+    );
+
+    fn run(&mut self, (scores, slots, mut log): Self::SystemData) {
+        if !log.playing {
+            let tick = log.tick;
+            for (score, slot) in (&scores, &slots).join() {
+                let Some(bird) = log.birds.get_mut(slot.0) else {
+                    continue;
+                };
+                if score.jumps as usize > bird.jumps.len() {
+                    bird.jumps.push(tick);
+                }
+            }
+        }
+        log.tick += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use specs::{Join, WorldExt};
+
+    use super::*;
+    use crate::game_state::GameState;
+    use crate::options::GameOptions;
+    use crate::resources::{HumanScore, ReplayFile};
+
+    /// Score of every bird still flying, by replay slot.
+    fn flying(gs: &GameState) -> BTreeMap<usize, BirdScore> {
+        (&gs.world.read_storage::<ReplaySlot>(), &gs.world.read_storage::<BirdScore>()).join()
+            .map(|(slot, score)| (slot.0, *score))
+            .collect()
+    }
+
+    /// Runs the round until every bird crashed. Returns the tick each one crashed on,
+    /// with the pipes and jumps it had the tick before, by replay slot.
+    fn fly_round(gs: &mut GameState) -> BTreeMap<usize, (u32, u32, u32)> {
+        let dt = 1.0 / gs.tick_rate() as f32;
+        let mut crashes = BTreeMap::new();
+        let mut before = flying(gs);
+        while !before.is_empty() {
+            gs.update(dt);
+            let tick = gs.world.read_resource::<ReplayLog>().tick;
+            assert!(tick < 36_000, "the round doesn't end");
+            let now = flying(gs);
+            for (slot, score) in before.iter().filter(|(slot, _)| !now.contains_key(slot)) {
+                crashes.insert(*slot, (tick, score.pipes, score.jumps));
+            }
+            before = now;
+        }
+        crashes
+    }
+
+    #[test]
+    fn test_replay_reproduces_recorded_round() {
+        // played back with the default options, the round recorded at 120 Hz still steps at 120 Hz
+        for tick_rate in [60, 120] {
+            let dir = std::env::temp_dir().join(format!("replay_round_test_{}_{}", tick_rate, std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            let path = dir.join("generation-00000.replay");
+
+            let mut recording = GameState::new(GameOptions { seed: Some(7), record_dir: Some(dir.clone()), tick_rate, ..GameOptions::default() });
+            recording.init().unwrap();
+            recording.start_run();
+            let recorded = fly_round(&mut recording);
+            // the round is saved when the next one starts
+            while !path.exists() {
+                recording.update(1.0 / tick_rate as f32);
+            }
+            assert_eq!(recorded.len(), ReplayFile::load(&path).unwrap().birds.len());
+
+            let mut replaying = GameState::new(GameOptions { replay: Some(path), ..GameOptions::default() });
+            replaying.init().unwrap();
+            assert_eq!(replaying.tick_rate(), tick_rate);
+            replaying.start_run();
+            let replayed = fly_round(&mut replaying);
+            std::fs::remove_dir_all(&dir).unwrap();
+
+            assert_eq!(replayed, recorded);
+            assert!(recorded.values().any(|(_, pipes, _)| *pipes > 0));
+            assert!(replaying.world.read_resource::<HumanScore>().last.is_none());
+        }
+    }
+}
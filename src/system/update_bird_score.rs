@@ -0,0 +1,29 @@
+use specs::{Join, Read, ReadStorage, System, WriteStorage};
+
+use crate::components::{BirdScore, PipeTarget, Transform};
+use crate::resources::{DeltaTime, GameConfig};
+
+/// Counts the time alive and the pipes passed of every bird still in the round.
+pub struct UpdateBirdScore;
+
+impl<'a> System<'a> for UpdateBirdScore {
+    type SystemData = (
+        WriteStorage<'a, BirdScore>,
+        ReadStorage<'a, PipeTarget>,
+        ReadStorage<'a, Transform>,
+        Read<'a, DeltaTime>,
+        Read<'a, GameConfig>
+    );
+
+    fn run(&mut self, (mut scores, pipe_targets, transforms, dt, config): Self::SystemData) {
+        // a pipe counts once its lower cap crosses the bird, `UpdatePipe` has already moved it this tick
+        let step = dt.0 * config.game_speed;
+        let pipes: Vec<f32> = (&pipe_targets, &transforms).join().map(|(_, transform)| transform.position[0]).collect();
+
+        for (score, transform) in (&mut scores, &transforms).join() {
+            let x = transform.position[0];
+            score.time_alive += dt.0;
+            score.pipes += pipes.iter().filter(|pipe_x| **pipe_x < x && **pipe_x + step >= x).count() as u32;
+        }
+    }
+}
@@ -1,8 +1,7 @@
-use specs::{Join, Read, System, WriteStorage};
+use specs::{Join, Read, ReadStorage, System, Write, WriteStorage};
 
-use crate::components::{ Player, Transform};
-use crate::game_configs::{GRAVITY, JUMP_FORCE};
-use crate::resources::{DeltaTime, InputHandler};
+use crate::components::{BirdScore, Human, Player, Transform};
+use crate::resources::{DeltaTime, GameConfig, InputHandler};
 
 pub struct UpdatePlayer;
 
@@ -10,20 +9,37 @@ impl<'a> System<'a> for UpdatePlayer {
     type SystemData = (
         WriteStorage<'a, Player>,
         WriteStorage<'a, Transform>,
-        Read<'a, InputHandler>,
-        Read<'a, DeltaTime>
+        WriteStorage<'a, BirdScore>,
+        ReadStorage<'a, Human>,
+        Write<'a, InputHandler>,
+        Read<'a, DeltaTime>,
+        Read<'a, GameConfig>
     );
 
-    fn run(&mut self, (mut players, mut tf, _, dt): Self::SystemData) {
-        for ( player, transform) in ( &mut players, &mut tf).join() {
+    fn run(&mut self, (mut players, mut tf, mut scores, humans, mut input, dt, config): Self::SystemData) {
+        if input.jump {
+            for (player, _) in (&mut players, &humans).join() {
+                player.jump = true;
+            }
+            input.jump = false;
+        }
+
+        for ( player, transform, score) in ( &mut players, &mut tf, (&mut scores).maybe()).join() {
+            // force is the vertical velocity in units per second, so the arc doesn't depend on the tick rate
             player.force = if player.jump {
                 player.jump = false;
-                JUMP_FORCE * dt.0
+                if let Some(score) = score {
+                    score.jumps += 1;
+                    if player.force > 0.0 {
+                        score.wasted_jumps += 1;
+                    }
+                }
+                config.jump_force
             } else {
-                player.force - GRAVITY * dt.0
+                player.force - config.gravity * dt.0
             };
 
-            transform.position[1] += player.force;
+            transform.position[1] += player.force * dt.0;
         }
     }
 }
\ No newline at end of file
@@ -4,7 +4,7 @@ use wasm_bindgen::prelude::*;
 #[cfg(target_arch = "wasm32")]
 #[wasm_bindgen(module = "/defined-in-js.js")]
 extern "C" {
-    pub fn render(gene: &str, pos: &str);
+    pub fn render(layers: &str, gene: &str, values: &str);
 }
 
 #[cfg(target_arch = "wasm32")]